@@ -0,0 +1,140 @@
+//! measures how long the sc takes to save a drone or node event, for networks of growing size
+//!
+//! run with `cargo bench --features benchmarks`
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ap24_simulation_controller::{MySimulationController, SimControllerOptions};
+use crossbeam_channel::{unbounded, Sender};
+use messages::node_event::NodeEvent;
+use wg_2024::config::{Client, Config, Drone, Server};
+use wg_2024::controller::DroneEvent;
+use wg_2024::network::{NodeId, SourceRoutingHeader};
+use wg_2024::packet::{Fragment, Packet, PacketType};
+
+const NODE_COUNTS: [usize; 3] = [25, 100, 250];
+/// fragments sent in each round, each one makes an event for its client and for every drone
+const FRAGMENTS: u64 = 10_000;
+/// drones crossed by every fragment
+const ROUTE_LEN: usize = 5;
+const ROUNDS: usize = 5;
+
+/// a ring of drones, with clients and servers each connected to two consecutive drones
+fn config(nodes: usize) -> Config {
+    let edge_nodes = (nodes / 25).max(1);
+    let drones = nodes - 2 * edge_nodes;
+    let id = |n: usize| NodeId::try_from(n).expect("at most 256 nodes");
+    let attached = |n: usize| vec![id(n % drones), id((n + 1) % drones)];
+    Config {
+        drone: (0..drones)
+            .map(|d| Drone {
+                id: id(d),
+                connected_node_ids: vec![id((d + 1) % drones)],
+                pdr: 0.0,
+            })
+            .collect(),
+        client: (0..edge_nodes)
+            .map(|c| Client {
+                id: id(drones + c),
+                connected_drone_ids: attached(c * 7),
+            })
+            .collect(),
+        server: (0..edge_nodes)
+            .map(|s| Server {
+                id: id(drones + edge_nodes + s),
+                connected_drone_ids: attached(s * 11 + 3),
+            })
+            .collect(),
+    }
+}
+
+/// events of `FRAGMENTS` fragments, each sent by a client and forwarded by the drones of its
+/// route towards a server
+fn events(cfg: &Config) -> (Vec<NodeEvent>, Vec<DroneEvent>) {
+    let mut node_events = Vec::new();
+    let mut drone_events = Vec::new();
+    let drones = cfg.drone.len();
+    for session_id in 0..FRAGMENTS {
+        let client = &cfg.client[session_id as usize % cfg.client.len()];
+        let server = &cfg.server[session_id as usize % cfg.server.len()];
+        let first = cfg
+            .drone
+            .iter()
+            .position(|d| d.id == client.connected_drone_ids[0])
+            .expect("clients are connected to drones");
+        let mut hops = vec![client.id];
+        hops.extend((0..ROUTE_LEN).map(|i| cfg.drone[(first + i) % drones].id));
+        hops.push(server.id);
+
+        let packet = |hop_index| Packet {
+            pack_type: PacketType::MsgFragment(Fragment {
+                fragment_index: 0,
+                total_n_fragments: 1,
+                length: 128,
+                data: [0; 128],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index,
+                hops: hops.clone(),
+            },
+            session_id,
+        };
+        node_events.push(NodeEvent::PacketSent(packet(1)));
+        drone_events.extend((2..hops.len()).map(|i| DroneEvent::PacketSent(packet(i))));
+    }
+    (node_events, drone_events)
+}
+
+fn controller(cfg: Config) -> (MySimulationController, Sender<DroneEvent>, Sender<NodeEvent>) {
+    let (droneevent_send, droneevent_recv) = unbounded();
+    let (nodeevent_send, nodeevent_recv) = unbounded();
+    let sc = MySimulationController::new(SimControllerOptions {
+        packet_send: HashMap::new(),
+        command_send: HashMap::new(),
+        droneevent_send: droneevent_send.clone(),
+        droneevent_recv,
+        nodeevent_recv,
+        node_handles: HashMap::new(),
+        config: cfg,
+        max_eventbuffer_dim: 1000,
+    });
+    (sc, droneevent_send, nodeevent_send)
+}
+
+fn main() {
+    println!("nodes  events/round  best ns/event  mean ns/event");
+    for nodes in NODE_COUNTS {
+        let cfg = config(nodes);
+        let (node_events, drone_events) = events(&cfg);
+        let (mut sc, droneevent_send, nodeevent_send) = controller(cfg);
+        let total = (node_events.len() + drone_events.len()) as u64;
+
+        let mut rounds = Vec::new();
+        // the first round fills the buffers of the nodes, so it is not measured
+        for round in 0..=ROUNDS {
+            for event in &node_events {
+                nodeevent_send.send(event.clone()).unwrap();
+            }
+            for event in &drone_events {
+                droneevent_send.send(event.clone()).unwrap();
+            }
+            let start = Instant::now();
+            let ingested = sc.ingest_pending();
+            let elapsed = start.elapsed();
+            assert_eq!(ingested, total, "every event should be ingested");
+            if round > 0 {
+                rounds.push(elapsed);
+            }
+        }
+
+        let per_event = |d: Duration| d.as_nanos() / u128::from(total);
+        let best = rounds.iter().copied().min().unwrap_or_default();
+        let mean = rounds.iter().sum::<Duration>() / ROUNDS as u32;
+        println!(
+            "{nodes:>5}  {total:>12}  {:>13}  {:>13}",
+            per_event(best),
+            per_event(mean)
+        );
+    }
+}
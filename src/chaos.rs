@@ -0,0 +1,248 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use wg_2024::network::NodeId;
+
+use crate::network::node_kind::NodeKind;
+use crate::network::Network;
+
+/// fault injected by the chaos engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChaosAction {
+    Crash(NodeId),
+    Pdr(NodeId, f32),
+    Connect(NodeId, NodeId),
+}
+
+impl Display for ChaosAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChaosAction::Crash(id) => write!(f, "crash #{id}"),
+            ChaosAction::Pdr(id, pdr) => write!(f, "pdr #{id} {pdr:.2}"),
+            ChaosAction::Connect(from, to) => write!(f, "connect #{from} #{to}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaosOptions {
+    pub seed: u64,
+    /// the time between two faults is chosen between these two
+    pub min_interval: Duration,
+    pub max_interval: Duration,
+    /// highest pdr that can be given to a drone
+    pub max_pdr: f32,
+    /// chaos stops after injecting this many faults
+    pub max_faults: Option<usize>,
+}
+
+impl ChaosOptions {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        ChaosOptions {
+            seed,
+            min_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(5),
+            max_pdr: 0.5,
+            max_faults: None,
+        }
+    }
+}
+
+/// injects random faults in the network, every random choice comes from a rng initialized with
+/// the seed, so given the same network the same seed always produces the same faults
+pub struct Chaos {
+    pub options: ChaosOptions,
+    rng: StdRng,
+    next_at: Instant,
+    /// faults injected so far, in order
+    pub history: Vec<ChaosAction>,
+}
+
+impl Chaos {
+    #[must_use]
+    pub fn new(options: ChaosOptions, now: Instant) -> Self {
+        let mut chaos = Chaos {
+            options,
+            rng: StdRng::seed_from_u64(options.seed),
+            next_at: now,
+            history: Vec::new(),
+        };
+        chaos.schedule(now);
+        chaos
+    }
+
+    /// chooses when the next fault is injected
+    fn schedule(&mut self, now: Instant) {
+        let min = self.options.min_interval.as_millis();
+        let max = self.options.max_interval.as_millis().max(min);
+        let wait = self.rng.random_range(min..=max);
+        self.next_at = now + Duration::from_millis(u64::try_from(wait).unwrap_or(u64::MAX));
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        now >= self.next_at
+    }
+
+    /// returns all the faults that could be injected in the network, in the order in which they
+    /// should be tried, the first one that keeps the network valid is the one to inject
+    pub fn candidates(&mut self, network: &Network) -> Vec<ChaosAction> {
+        let mut alive: Vec<NodeId> = network
+            .nodes()
+            .iter()
+            .filter(|n| !matches!(n.kind, NodeKind::Drone { crashed: true, .. }))
+            .map(|n| n.id)
+            .collect();
+        alive.sort_unstable();
+        let drones: Vec<NodeId> = alive
+            .iter()
+            .copied()
+            .filter(|id| {
+                matches!(
+                    network.get_node_from_id(*id).map(|n| n.kind),
+                    Some(NodeKind::Drone { .. })
+                )
+            })
+            .collect();
+
+        let mut crashes: Vec<ChaosAction> =
+            drones.iter().map(|id| ChaosAction::Crash(*id)).collect();
+        let max_pdr = (self.options.max_pdr.clamp(0.0, 1.0) * 100.0).round() as u32;
+        let mut pdrs: Vec<ChaosAction> = drones
+            .iter()
+            .map(|id| {
+                let pdr = self.rng.random_range(0..=max_pdr) as f32 / 100.0;
+                ChaosAction::Pdr(*id, pdr)
+            })
+            .collect();
+        let mut connections: Vec<ChaosAction> = Vec::new();
+        for (i, from) in alive.iter().enumerate() {
+            for to in &alive[i + 1..] {
+                if !network.graph().has_edge(*from, *to) {
+                    connections.push(ChaosAction::Connect(*from, *to));
+                }
+            }
+        }
+
+        crashes.shuffle(&mut self.rng);
+        pdrs.shuffle(&mut self.rng);
+        connections.shuffle(&mut self.rng);
+        let mut kinds = [crashes, pdrs, connections];
+        kinds.shuffle(&mut self.rng);
+        kinds.into_iter().flatten().collect()
+    }
+}
+
+impl crate::MySimulationController {
+    /// starts injecting random faults in the network, replacing the chaos engine that was
+    /// running, can be called before `run`
+    pub fn start_chaos(&mut self, options: ChaosOptions) {
+        info!("starting chaos with {options:?}");
+        self.chaos = Some(Chaos::new(options, Instant::now()));
+    }
+
+    pub(crate) fn stop_chaos(&mut self) -> bool {
+        match self.chaos.take() {
+            Some(chaos) => {
+                info!(
+                    "stopped chaos with seed {}, injected faults: {:?}",
+                    chaos.options.seed, chaos.history
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// injects a fault if the chaos engine is running and it is time to do so, faults that
+    /// would make the network invalid are skipped
+    pub(crate) fn run_chaos(&mut self) {
+        let now = Instant::now();
+        let Some(mut chaos) = self.chaos.take() else {
+            return;
+        };
+        if chaos.is_due(now) {
+            let applied = chaos
+                .candidates(&self.network)
+                .into_iter()
+                .find(|action| self.apply_chaos_action(*action));
+            match applied {
+                Some(action) => {
+                    chaos.history.push(action);
+                    info!(
+                        "chaos (seed {}) fault #{}: {action}",
+                        chaos.options.seed,
+                        chaos.history.len()
+                    );
+                }
+                None => warn!("chaos could not find any fault that keeps the network valid"),
+            }
+            chaos.schedule(now);
+        }
+        if chaos
+            .options
+            .max_faults
+            .is_some_and(|max| chaos.history.len() >= max)
+        {
+            info!(
+                "chaos with seed {} injected all its faults: {:?}",
+                chaos.options.seed, chaos.history
+            );
+            return;
+        }
+        self.chaos = Some(chaos);
+    }
+
+    /// returns whether the action was applied
+    fn apply_chaos_action(&mut self, action: ChaosAction) -> bool {
+        match action {
+            ChaosAction::Crash(id) => {
+                let ok = self.crash(id).is_ok();
+                self.screen.marked.remove(&id);
+                self.refresh_focus();
+                ok
+            }
+            ChaosAction::Pdr(id, pdr) => {
+                self.change_pdr(id, pdr);
+                true
+            }
+            ChaosAction::Connect(from, to) => self.add_connection(from, to).is_ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::tests::line_network as network;
+
+    #[test]
+    fn same_seed_same_faults() {
+        let network = network();
+        let now = Instant::now();
+        let mut a = Chaos::new(ChaosOptions::new(42), now);
+        let mut b = Chaos::new(ChaosOptions::new(42), now);
+        assert_eq!(a.next_at, b.next_at);
+        for _ in 0..5 {
+            assert_eq!(a.candidates(&network), b.candidates(&network));
+        }
+    }
+
+    #[test]
+    fn candidates() {
+        let network = network();
+        let mut chaos = Chaos::new(ChaosOptions::new(7), Instant::now());
+        let candidates = chaos.candidates(&network);
+        // 4 crashes, 4 pdrs, 15 pairs of the 6 nodes minus the 6 existing edges
+        assert_eq!(candidates.len(), 4 + 4 + 9);
+        assert!(!candidates.contains(&ChaosAction::Connect(1, 2)));
+        assert!(candidates.iter().all(|a| match a {
+            ChaosAction::Pdr(_, pdr) => (0.0..=0.5).contains(pdr),
+            _ => true,
+        }));
+    }
+}
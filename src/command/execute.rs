@@ -0,0 +1,90 @@
+use log::info;
+use wg_2024::network::NodeId;
+
+use crate::network::node_kind::NodeKind;
+use crate::stats_export::DEFAULT_STATS_DIR;
+
+use super::Command;
+
+impl crate::MySimulationController {
+    /// parses and runs a line typed in the command prompt, returns an error describing why the
+    /// command could not be run
+    pub(crate) fn execute_command(&mut self, input: &str) -> Result<(), String> {
+        let command = super::parse(input)?;
+        info!("executing command {command:?}");
+        match command {
+            Command::Crash(id) => {
+                self.expect_drone(id, false)?;
+                self.crash(id)?;
+                self.refresh_focus();
+            }
+            Command::Pdr(id, pdr) => {
+                self.expect_drone(id, true)?;
+                self.change_pdr(id, pdr);
+            }
+            Command::Connect(from, to) => {
+                self.expect_node(from)?;
+                self.expect_node(to)?;
+                self.add_connection(from, to)?;
+            }
+            Command::Spawn { id, pdr, neighbors } => {
+                self.spawn_drone(id, pdr, &neighbors)?;
+            }
+            Command::Revive { id, neighbors } => {
+                self.expect_drone(id, false)?;
+                self.revive_drone(id, neighbors.as_deref())?;
+            }
+            Command::Select(id) => {
+                self.expect_node(id)?;
+                self.select_node(id);
+            }
+            Command::Sweep(id, kind) => {
+                self.expect_drone(id, true)?;
+                self.start_pdr_sweep(id, kind);
+            }
+            Command::StopSweep(id) => {
+                if !self.stop_pdr_sweep(id) {
+                    return Err(format!("drone #{id} has no sweep running"));
+                }
+            }
+            Command::Chaos(options) => self.start_chaos(options),
+            Command::StopChaos => {
+                if !self.stop_chaos() {
+                    return Err("chaos is not running".to_string());
+                }
+            }
+            Command::Shortcut(policy) => self.set_shortcut_policy(policy),
+            Command::ExportStats(dir) => {
+                let dir = dir
+                    .or_else(|| self.stats_dir.clone())
+                    .unwrap_or_else(|| DEFAULT_STATS_DIR.into());
+                self.export_stats(&dir)?;
+            }
+            Command::ExportTopology(path) => {
+                let cfg = toml::to_string(&self.network.to_config())
+                    .map_err(|e| format!("could not serialize topology: {e}"))?;
+                std::fs::write(&path, cfg)
+                    .map_err(|e| format!("could not write {}: {e}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn expect_node(&self, id: NodeId) -> Result<NodeKind, String> {
+        self.network
+            .get_node_from_id(id)
+            .map(|n| n.kind)
+            .ok_or_else(|| format!("node #{id} is not in the network"))
+    }
+
+    /// checks that the node is a drone, and if `alive` is set that it has not crashed
+    fn expect_drone(&self, id: NodeId, alive: bool) -> Result<(), String> {
+        match self.expect_node(id)? {
+            NodeKind::Drone { crashed: true, .. } if alive => {
+                Err(format!("drone #{id} has crashed"))
+            }
+            NodeKind::Drone { .. } => Ok(()),
+            _ => Err(format!("node #{id} is not a drone")),
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::{path::PathBuf, time::Duration};
+
+use wg_2024::network::NodeId;
+
+use crate::chaos::ChaosOptions;
+use crate::pdr_sweep::SweepKind;
+use crate::shortcut::ShortcutPolicy;
+
+mod execute;
+
+/// commands that can be typed in the command prompt
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Crash(NodeId),
+    Pdr(NodeId, f32),
+    Connect(NodeId, NodeId),
+    Spawn {
+        id: NodeId,
+        pdr: f32,
+        neighbors: Vec<NodeId>,
+    },
+    /// `None` restores the neighbors the drone had before crashing
+    Revive {
+        id: NodeId,
+        neighbors: Option<Vec<NodeId>>,
+    },
+    Select(NodeId),
+    ExportTopology(PathBuf),
+    /// exports the statistics to the given directory or to the configured one
+    ExportStats(Option<PathBuf>),
+    Sweep(NodeId, SweepKind),
+    StopSweep(NodeId),
+    Chaos(ChaosOptions),
+    StopChaos,
+    Shortcut(ShortcutPolicy),
+}
+
+/// names of the commands, used for tab completion
+pub const COMMAND_NAMES: [&str; 10] = [
+    "crash", "pdr", "connect", "spawn", "revive", "select", "export", "sweep", "chaos", "shortcut",
+];
+
+/// pdr given to spawned drones when none is specified
+pub const DEFAULT_SPAWN_PDR: f32 = 0.05;
+
+/// parses the line typed in the command prompt, if it is not a valid command returns an error
+/// that can be shown to the user
+pub fn parse(input: &str) -> Result<Command, String> {
+    let mut words = input.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("empty command".to_string());
+    };
+    let args: Vec<&str> = words.collect();
+
+    let command = match (name, args.as_slice()) {
+        ("crash", [id]) => Command::Crash(parse_id(id)?),
+        ("pdr", [id, pdr]) => Command::Pdr(parse_id(id)?, parse_pdr(pdr)?),
+        ("connect", [from, to]) => Command::Connect(parse_id(from)?, parse_id(to)?),
+        ("spawn", [id, options @ ..]) => {
+            let mut pdr = DEFAULT_SPAWN_PDR;
+            let mut neighbors = Vec::new();
+            for option in options {
+                match option.split_once('=') {
+                    Some(("pdr", value)) => pdr = parse_pdr(value)?,
+                    Some(("neighbors", value)) => neighbors = parse_ids(value)?,
+                    _ => return Err(format!("unknown option `{option}` for spawn")),
+                }
+            }
+            Command::Spawn {
+                id: parse_id(id)?,
+                pdr,
+                neighbors,
+            }
+        }
+        ("revive", [id]) => Command::Revive {
+            id: parse_id(id)?,
+            neighbors: None,
+        },
+        ("revive", [id, option]) => match option.split_once('=') {
+            Some(("neighbors", value)) => Command::Revive {
+                id: parse_id(id)?,
+                neighbors: Some(parse_ids(value)?),
+            },
+            _ => return Err(format!("unknown option `{option}` for revive")),
+        },
+        ("select", [id]) => Command::Select(parse_id(id)?),
+        ("export", ["topology", path]) => Command::ExportTopology(PathBuf::from(path)),
+        ("export", ["stats"]) => Command::ExportStats(None),
+        ("export", ["stats", dir]) => Command::ExportStats(Some(PathBuf::from(dir))),
+        ("sweep", [id, "ramp", from, to, secs]) => Command::Sweep(
+            parse_id(id)?,
+            SweepKind::Ramp {
+                from: parse_pdr(from)?,
+                to: parse_pdr(to)?,
+                duration: parse_secs(secs)?,
+            },
+        ),
+        ("sweep", [id, "steps", secs, values]) => {
+            let values: Vec<f32> = values
+                .split(',')
+                .filter(|x| !x.is_empty())
+                .map(parse_pdr)
+                .collect::<Result<_, _>>()?;
+            if values.is_empty() {
+                return Err("a sweep needs at least one pdr".to_string());
+            }
+            Command::Sweep(
+                parse_id(id)?,
+                SweepKind::Steps {
+                    values,
+                    interval: parse_secs(secs)?,
+                },
+            )
+        }
+        ("sweep", [id, "stop"]) => Command::StopSweep(parse_id(id)?),
+        ("chaos", ["stop"]) => Command::StopChaos,
+        ("chaos", [seed, options @ ..]) => {
+            let seed = seed
+                .parse::<u64>()
+                .map_err(|_| format!("`{seed}` is not a valid seed"))?;
+            let mut chaos = ChaosOptions::new(seed);
+            for option in options {
+                match option.split_once('=') {
+                    Some(("interval", value)) => {
+                        let Some((min, max)) = value.split_once('-') else {
+                            return Err(format!("`{value}` is not an interval like 1-5"));
+                        };
+                        chaos.min_interval = parse_secs(min)?;
+                        chaos.max_interval = parse_secs(max)?;
+                        if chaos.min_interval > chaos.max_interval {
+                            return Err(format!("`{value}` is not an interval like 1-5"));
+                        }
+                    }
+                    Some(("pdr", value)) => chaos.max_pdr = parse_pdr(value)?,
+                    Some(("faults", value)) => {
+                        chaos.max_faults = Some(
+                            value
+                                .parse::<usize>()
+                                .map_err(|_| format!("`{value}` is not a number of faults"))?,
+                        );
+                    }
+                    _ => return Err(format!("unknown option `{option}` for chaos")),
+                }
+            }
+            Command::Chaos(chaos)
+        }
+        ("shortcut", ["immediate"]) => Command::Shortcut(ShortcutPolicy::Immediate),
+        ("shortcut", ["delay", secs]) => {
+            Command::Shortcut(ShortcutPolicy::Delayed(parse_secs(secs)?))
+        }
+        ("shortcut", ["drop"]) => Command::Shortcut(ShortcutPolicy::Drop),
+        ("shortcut", ["reroute"]) => Command::Shortcut(ShortcutPolicy::Reroute),
+        ("crash", _) => return Err("usage: crash <id>".to_string()),
+        ("pdr", _) => return Err("usage: pdr <id> <pdr>".to_string()),
+        ("connect", _) => return Err("usage: connect <id> <id>".to_string()),
+        ("spawn", _) => {
+            return Err("usage: spawn <id> [pdr=<pdr>] [neighbors=<id>,<id>...]".to_string())
+        }
+        ("revive", _) => return Err("usage: revive <id> [neighbors=<id>,<id>...]".to_string()),
+        ("select", _) => return Err("usage: select <id>".to_string()),
+        ("shortcut", _) => {
+            return Err("usage: shortcut immediate | delay <secs> | drop | reroute".to_string())
+        }
+        ("export", _) => return Err("usage: export topology <file> | stats [dir]".to_string()),
+        ("chaos", _) => {
+            return Err(
+                "usage: chaos <seed> [interval=<min>-<max>] [pdr=<pdr>] [faults=<n>] | stop"
+                    .to_string(),
+            )
+        }
+        ("sweep", _) => {
+            return Err(
+                "usage: sweep <id> ramp <from> <to> <secs> | steps <secs> <pdr>,... | stop"
+                    .to_string(),
+            )
+        }
+        (name, _) => return Err(format!("unknown command `{name}`")),
+    };
+    Ok(command)
+}
+
+fn parse_id(s: &str) -> Result<NodeId, String> {
+    s.parse::<NodeId>()
+        .map_err(|_| format!("`{s}` is not a valid node id"))
+}
+
+/// a comma separated list of ids, each one at most once
+fn parse_ids(s: &str) -> Result<Vec<NodeId>, String> {
+    let mut ids = Vec::new();
+    for id in s.split(',').filter(|x| !x.is_empty()).map(parse_id) {
+        let id = id?;
+        if ids.contains(&id) {
+            return Err(format!("`{id}` is repeated in `{s}`"));
+        }
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+pub(crate) fn parse_pdr(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(pdr) if (0.0..=1.0).contains(&pdr) => Ok(pdr),
+        _ => Err(format!("`{s}` is not a pdr between 0 and 1")),
+    }
+}
+
+fn parse_secs(s: &str) -> Result<Duration, String> {
+    s.parse::<f32>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f32(secs).ok())
+        .ok_or_else(|| format!("`{s}` is not a valid number of seconds"))
+}
+
+/// completes the last word of the input, either with the name of a command or with one of the
+/// given node ids, returns the completed input and the candidates that matched
+pub fn complete(input: &str, ids: &[NodeId]) -> (String, Vec<String>) {
+    let start = input
+        .rfind(|c: char| c.is_whitespace() || c == ',' || c == '=')
+        .map_or(0, |i| i + 1);
+    let (head, word) = input.split_at(start);
+
+    let words: Vec<&str> = head.split_whitespace().collect();
+    let keywords: &[&str] = match words.as_slice() {
+        [] => &COMMAND_NAMES,
+        ["export"] => &["topology", "stats"],
+        ["sweep", _] => &["ramp", "steps", "stop"],
+        ["chaos"] => &["stop"],
+        _ => &[],
+    };
+    let candidates: Vec<String> = if keywords.is_empty() {
+        ids.iter()
+            .map(ToString::to_string)
+            .filter(|id| id.starts_with(word))
+            .collect()
+    } else {
+        keywords
+            .iter()
+            .filter(|name| name.starts_with(word))
+            .map(|name| (*name).to_string())
+            .collect()
+    };
+
+    let completed = match candidates.as_slice() {
+        [] => input.to_string(),
+        [single] => format!("{head}{single}"),
+        [first, rest @ ..] => {
+            let mut prefix = first.as_str();
+            for c in rest {
+                while !c.starts_with(prefix) {
+                    prefix = &prefix[..prefix.len() - 1];
+                }
+            }
+            format!("{head}{prefix}")
+        }
+    };
+    (completed, candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(parse("crash 4"), Ok(Command::Crash(4)));
+        assert_eq!(parse(" pdr 3  0.25 "), Ok(Command::Pdr(3, 0.25)));
+        assert_eq!(parse("connect 2 9"), Ok(Command::Connect(2, 9)));
+        assert_eq!(
+            parse("spawn 30 pdr=0.1 neighbors=2,5"),
+            Ok(Command::Spawn {
+                id: 30,
+                pdr: 0.1,
+                neighbors: vec![2, 5]
+            })
+        );
+        assert_eq!(
+            parse("spawn 30"),
+            Ok(Command::Spawn {
+                id: 30,
+                pdr: DEFAULT_SPAWN_PDR,
+                neighbors: vec![]
+            })
+        );
+        assert_eq!(parse("select 11"), Ok(Command::Select(11)));
+        assert_eq!(
+            parse("revive 4"),
+            Ok(Command::Revive {
+                id: 4,
+                neighbors: None
+            })
+        );
+        assert_eq!(
+            parse("revive 4 neighbors=3,5"),
+            Ok(Command::Revive {
+                id: 4,
+                neighbors: Some(vec![3, 5])
+            })
+        );
+        assert_eq!(
+            parse("export topology out.toml"),
+            Ok(Command::ExportTopology(PathBuf::from("out.toml")))
+        );
+        assert_eq!(
+            parse("sweep 3 ramp 0.05 0.9 60"),
+            Ok(Command::Sweep(
+                3,
+                SweepKind::Ramp {
+                    from: 0.05,
+                    to: 0.9,
+                    duration: Duration::from_secs(60)
+                }
+            ))
+        );
+        assert_eq!(
+            parse("sweep 3 steps 0.5 0.1,0.2"),
+            Ok(Command::Sweep(
+                3,
+                SweepKind::Steps {
+                    values: vec![0.1, 0.2],
+                    interval: Duration::from_millis(500)
+                }
+            ))
+        );
+        assert_eq!(parse("export stats"), Ok(Command::ExportStats(None)));
+        assert_eq!(
+            parse("export stats run1"),
+            Ok(Command::ExportStats(Some(PathBuf::from("run1"))))
+        );
+        assert_eq!(parse("sweep 3 stop"), Ok(Command::StopSweep(3)));
+        assert_eq!(
+            parse("chaos 42 interval=0.5-2 pdr=0.3"),
+            Ok(Command::Chaos(ChaosOptions {
+                seed: 42,
+                min_interval: Duration::from_millis(500),
+                max_interval: Duration::from_secs(2),
+                max_pdr: 0.3,
+                max_faults: None
+            }))
+        );
+        assert_eq!(parse("chaos stop"), Ok(Command::StopChaos));
+        assert_eq!(
+            parse("shortcut delay 0.5"),
+            Ok(Command::Shortcut(ShortcutPolicy::Delayed(
+                Duration::from_millis(500)
+            )))
+        );
+        assert_eq!(
+            parse("shortcut reroute"),
+            Ok(Command::Shortcut(ShortcutPolicy::Reroute))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("crash").is_err());
+        assert!(parse("crash 256").is_err());
+        assert!(parse("pdr 3 1.5").is_err());
+        assert!(parse("spawn 30 speed=3").is_err());
+        assert!(parse("spawn 30 neighbors=2,2").is_err());
+        assert!(parse("revive 4 neighbors=1,3,1").is_err());
+        assert!(parse("fly 3").is_err());
+        assert!(parse("revive 4 pdr=0.1").is_err());
+        assert!(parse("shortcut delay").is_err());
+        assert!(parse("sweep 3 ramp 0.1 0.2 -1").is_err());
+        assert!(parse("sweep 3 steps 1 ,").is_err());
+        assert!(parse("chaos 42 interval=5-1").is_err());
+        assert!(parse("chaos -1").is_err());
+    }
+
+    #[test]
+    fn complete_words() {
+        let ids = [1, 10, 11, 2];
+        assert_eq!(complete("cr", &ids).0, "crash");
+        assert_eq!(complete("s", &ids).0, "s");
+        assert_eq!(complete("crash 2", &ids).0, "crash 2");
+        assert_eq!(complete("connect 2 1", &ids).0, "connect 2 1");
+        assert_eq!(complete("connect 2 1", &ids).1.len(), 3);
+        assert_eq!(complete("pdr 1", &ids).1, vec!["1", "10", "11"]);
+        assert_eq!(
+            complete("spawn 3 neighbors=2,1", &ids).0,
+            "spawn 3 neighbors=2,1"
+        );
+        assert_eq!(complete("export t", &ids).0, "export topology");
+        assert_eq!(complete("sweep 1 st", &ids).1, vec!["steps", "stop"]);
+    }
+}
@@ -0,0 +1,50 @@
+use std::{any::Any, fmt::Debug, time::Instant};
+
+use log::error;
+use wg_2024::network::NodeId;
+
+/// a malformed event that the sc could not handle, kept with the event that caused it
+#[derive(Debug, Clone)]
+pub struct Incident {
+    pub at: Instant,
+    /// node that sent the event, when it could be found
+    pub source: Option<NodeId>,
+    pub message: String,
+    /// the offending event, formatted with `Debug`
+    pub event: String,
+}
+
+/// the message a thread panicked with, when it is a string
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic payload is not a string")
+}
+
+impl crate::MySimulationController {
+    /// in strict mode a malformed event or a node thread that exits makes the sc panic, like it
+    /// used to, instead of being recorded as an incident or marking the node as failed
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// records an incident caused by the given event, the oldest incidents are discarded once
+    /// there are more than `max_eventbuffer_dim`
+    /// # Panics
+    /// panics with the message of the incident in strict mode
+    pub(crate) fn incident(&mut self, source: Option<NodeId>, message: String, event: &dyn Debug) {
+        if self.strict {
+            panic!("{message}, caused by {event:?}");
+        }
+        error!("incident: {message}, caused by {event:?}");
+        self.incidents.push_front(Incident {
+            at: Instant::now(),
+            source,
+            message,
+            event: format!("{event:#?}"),
+        });
+        self.incidents.truncate(self.max_eventbuffer_dim);
+    }
+}
@@ -1,26 +1,22 @@
+use crate::history::spill_or_log;
+use crate::network::conformance::check_drone_event;
+use crate::network::history::HistoryTable;
+use crate::network::node_kind::NodeKind;
+use crate::network::node_stats::count_message;
+use crate::network::topology_diff::valid_known_network_graph;
 use crate::screen::{self};
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use messages::node_event::NodeEvent;
 use screen::Window;
+use std::time::Instant;
 
 use wg_2024::{controller::DroneEvent, packet::PacketType};
 
-use messages::node_event::EventNetworkGraph;
-fn valid_known_network_graph(g: &EventNetworkGraph) -> bool {
-    for n in &g.nodes {
-        for a in &n.neighbors {
-            if g.nodes.get(usize::from(*a)).is_none() {
-                return false;
-            }
-        }
-    }
-    true
-}
-
 impl crate::MySimulationController {
     pub(crate) fn save_nodeevent(&mut self, event: NodeEvent) {
         let Some(src) = event.source() else {
-            panic!("event has no source, caused by {:?}", event);
+            self.incident(None, "event has no source".to_string(), &event);
+            return;
         };
 
         // -------------------------------------------------------------------------
@@ -44,9 +40,7 @@ impl crate::MySimulationController {
                 }
                 _ => packet.routing_header.current_hop(),
             } {
-                if self.network.edges.contains_key(&(src, dst))
-                    || self.network.edges.contains_key(&(dst, src))
-                {
+                if self.network.graph().has_edge(src, dst) {
                     self.network
                         .update_edge_activity(src, dst, packet.pack_type.clone());
                 }
@@ -56,7 +50,9 @@ impl crate::MySimulationController {
         // -------------------------------------------------------------------------
         // fix scrolling pushdown on certain tabs
         // -------------------------------------------------------------------------
-        if let Window::Detail { tab } = self.screen.window {
+        // pages of the history do not move
+        if let (Window::Detail { tab }, None) = (&self.screen.window, &self.history_page) {
+            let tab = *tab;
             match event {
                 NodeEvent::PacketSent(_) if tab == 0 && src == self.screen.focus => {
                     self.packet_table_state.scroll_down_by(1);
@@ -77,6 +73,35 @@ impl crate::MySimulationController {
             }
         }
 
+        // -------------------------------------------------------------------------
+        // sample the traffic for the metrics window
+        // -------------------------------------------------------------------------
+        let throughput = &mut self.network.throughput;
+        match &event {
+            NodeEvent::PacketSent(packet) => {
+                throughput.record_packet(src, &packet.pack_type, Instant::now());
+                self.network
+                    .floods
+                    .record(&packet.pack_type, Instant::now());
+                self.network.nacks.record(src, packet);
+                if let PacketType::MsgFragment(fragment) = &packet.pack_type {
+                    self.network.record_fragment(
+                        src,
+                        packet.session_id,
+                        fragment,
+                        self.payload_decoder,
+                    );
+                }
+            }
+            NodeEvent::StartingMessageTransmission(_) => {
+                throughput.record_message_started(src, Instant::now());
+            }
+            NodeEvent::MessageSentSuccessfully(_) => {
+                throughput.record_message_completed(src, Instant::now());
+            }
+            _ => {}
+        }
+
         // -------------------------------------------------------------------------
         // update each node with the received information of the event
         // -------------------------------------------------------------------------
@@ -89,9 +114,12 @@ impl crate::MySimulationController {
             match event {
                 NodeEvent::PacketSent(packet) => {
                     trace!("Client/Server #{src} sent event PacketSent with packet {packet}");
+                    node.stats.sent.add(&packet.pack_type);
                     node.sent.push_front(packet.clone());
-                    if node.sent.len() > self.max_eventbuffer_dim {
-                        node.sent.pop_back();
+                    if node.sent.len() > self.history.retention.sent {
+                        if let Some(old) = node.sent.pop_back() {
+                            spill_or_log(&mut self.history, src, HistoryTable::Sent, &old);
+                        }
                     }
                 }
                 NodeEvent::MessageSentSuccessfully(message) => {
@@ -100,9 +128,14 @@ impl crate::MySimulationController {
                         message
                     );
                     if node.msent.contains_key(&message.session_id) {
+                        count_message(&mut node.stats.messages_completed, &message.content);
                         node.msent.insert(message.session_id, (message, true));
+                    } else if node.msent_evicted.remove(&message.session_id) {
+                        // started long ago, it was moved out of memory while still in flight
+                        count_message(&mut node.stats.messages_completed, &message.content);
                     } else {
-                        panic!("Got a MessageSentSuccessfully from #{src} with sid #{}, but didn't receive any StartingMessageTransmission for the same message yet",message.session_id)
+                        let text = format!("Got a MessageSentSuccessfully from #{src} with sid #{}, but didn't receive any StartingMessageTransmission for the same message yet", message.session_id);
+                        self.incident(Some(src), text, &message);
                     }
                 }
                 NodeEvent::StartingMessageTransmission(message) => {
@@ -110,10 +143,18 @@ impl crate::MySimulationController {
                         "Client/Server #{src} sent event StartingMessageTransmission with Message {:?}",
                         message
                     );
+                    count_message(&mut node.stats.messages_started, &message.content);
                     node.msent.insert(message.session_id, (message, false));
 
-                    if node.msent.len() > self.max_eventbuffer_dim {
-                        node.msent.pop();
+                    if node.msent.len() > self.history.retention.messages {
+                        // the oldest completed message goes first, then the oldest in flight
+                        let idx = node.msent.values().position(|(_, done)| *done).unwrap_or(0);
+                        if let Some((session_id, old)) = node.msent.shift_remove_index(idx) {
+                            if !old.1 {
+                                node.msent_evicted.insert(session_id);
+                            }
+                            spill_or_log(&mut self.history, src, HistoryTable::MessagesSent, &old);
+                        }
                     }
                 }
                 NodeEvent::MessageReceived(message) => {
@@ -124,10 +165,14 @@ impl crate::MySimulationController {
                         "Client/Server #{dst} sent event MessageReceived with Message {:?}",
                         message
                     );
+                    count_message(&mut node.stats.messages_received, &message.content);
                     node.mreceived.push_front(message);
 
-                    if node.mreceived.len() > self.max_eventbuffer_dim {
-                        node.mreceived.pop_back();
+                    if node.mreceived.len() > self.history.retention.messages {
+                        if let Some(old) = node.mreceived.pop_back() {
+                            let table = HistoryTable::MessagesReceived;
+                            spill_or_log(&mut self.history, dst, table, &old);
+                        }
                     }
                 }
                 NodeEvent::KnownNetworkGraph { source: _, graph } => {
@@ -157,23 +202,20 @@ impl crate::MySimulationController {
             | DroneEvent::PacketDropped(ref packet)
             | DroneEvent::ControllerShortcut(ref packet) => packet,
         };
-        let id: u8 = match (&packet.pack_type, &event) {
+        let id = match (&packet.pack_type, &event) {
             (PacketType::FloodRequest(flood_request), _) => {
-                flood_request
-                    .path_trace
-                    .last()
-                    .unwrap_or_else(|| panic!("path trace is empty, got {packet}"))
-                    .0
+                flood_request.path_trace.last().map(|(id, _)| *id)
             }
-            (_, DroneEvent::PacketDropped(_)) => {
-                packet.routing_header.current_hop().unwrap_or_else(|| {
-                    panic!("could not find previous hop in packet {packet} for event {event:?}")
-                })
-            }
-
-            _ => packet.routing_header.previous_hop().unwrap_or_else(|| {
-                panic!("could not find previous hop in packet {packet} for event {event:?}")
-            }),
+            (_, DroneEvent::PacketDropped(_)) => packet.routing_header.current_hop(),
+            _ => packet.routing_header.previous_hop(),
+        };
+        let Some(id) = id else {
+            let message = match packet.pack_type {
+                PacketType::FloodRequest(_) => format!("path trace is empty, got {packet}"),
+                _ => format!("could not find previous hop in packet {packet}"),
+            };
+            self.incident(None, message, &event);
+            return;
         };
 
         if let DroneEvent::PacketSent(_) = event {
@@ -191,52 +233,102 @@ impl crate::MySimulationController {
                 }
                 _ => packet.routing_header.current_hop(),
             } {
-                if self.network.edges.contains_key(&(id, dst))
-                    || self.network.edges.contains_key(&(dst, id))
-                {
+                if self.network.graph().has_edge(id, dst) {
                     self.network
                         .update_edge_activity(id, dst, packet.pack_type.clone());
                 }
             };
         };
 
+        // check the event against the protocol, a violation can be blamed on another drone
+        let violations = check_drone_event(&self.network, id, &event);
+        if let Some(node) = self.network.get_mut_node_from_id(id) {
+            node.conformance.checked += 1;
+        }
+        for violation in violations {
+            warn!("Drone {} broke the protocol, {violation}", violation.drone);
+            if let Some(node) = self.network.get_mut_node_from_id(violation.drone) {
+                node.conformance.record(violation);
+            }
+        }
+
+        // shortcutted packets are delivered by the sc, so they are part of the traffic too
+        if let DroneEvent::PacketSent(_) | DroneEvent::ControllerShortcut(_) = event {
+            self.network
+                .throughput
+                .record_packet(id, &packet.pack_type, Instant::now());
+            self.network
+                .floods
+                .record(&packet.pack_type, Instant::now());
+            self.network.nacks.record(id, packet);
+        }
+
         if let Some(node) = self.network.get_mut_node_from_id(id) {
             match event {
                 DroneEvent::PacketSent(packet) => {
                     trace!("Drone {id} sent event PacketSent with packet {packet}");
+                    node.stats.sent.add(&packet.pack_type);
                     if let PacketType::MsgFragment(_) = packet.pack_type {
                         node.n_frags_sent = node.n_frags_sent.saturating_add(1);
+                        node.drop_window.record(Instant::now(), false);
+                        if let NodeKind::Drone { pdr, .. } = node.kind {
+                            node.drop_check.record_fragment(pdr, false);
+                        }
                     }
                     node.sent.push_front(packet);
-                    if let Window::Detail { tab: 0 } = self.screen.window {
+                    if let (Window::Detail { tab: 0 }, None) =
+                        (&self.screen.window, &self.history_page)
+                    {
                         self.packet_table_state.scroll_down_by(1);
                     };
 
-                    if node.sent.len() > self.max_eventbuffer_dim {
-                        node.sent.pop_back();
+                    if node.sent.len() > self.history.retention.sent {
+                        if let Some(old) = node.sent.pop_back() {
+                            spill_or_log(&mut self.history, id, HistoryTable::Sent, &old);
+                        }
                     }
                 }
                 DroneEvent::PacketDropped(packet) => {
                     trace!("Drone {id} sent event PacketDropped with packet {packet}");
-                    node.n_frags_dropped = node.n_frags_dropped.saturating_add(1);
-                    node.n_frags_sent = node.n_frags_sent.saturating_add(1);
+                    node.stats.dropped.add(&packet.pack_type);
+                    if let PacketType::MsgFragment(_) = packet.pack_type {
+                        node.n_frags_dropped = node.n_frags_dropped.saturating_add(1);
+                        node.n_frags_sent = node.n_frags_sent.saturating_add(1);
+                        node.drop_window.record(Instant::now(), true);
+                        if let NodeKind::Drone { pdr, .. } = node.kind {
+                            node.drop_check.record_fragment(pdr, true);
+                        }
+                    } else {
+                        // only fragments can be dropped, everything else must be delivered
+                        warn!("Drone {id} dropped a packet that is not a fragment: {packet}");
+                        node.drop_check.record_non_fragment_drop();
+                    }
                     node.dropped.push_front(packet);
-                    if let Window::Detail { tab: 1 } = self.screen.window {
+                    if let (Window::Detail { tab: 1 }, None) =
+                        (&self.screen.window, &self.history_page)
+                    {
                         self.packet_table_state.scroll_down_by(1);
                     };
-                    if node.dropped.len() > self.max_eventbuffer_dim {
-                        node.dropped.pop_back();
+                    if node.dropped.len() > self.history.retention.dropped {
+                        if let Some(old) = node.dropped.pop_back() {
+                            spill_or_log(&mut self.history, id, HistoryTable::Dropped, &old);
+                        }
                     }
                 }
                 DroneEvent::ControllerShortcut(packet) => {
                     debug!("Drone {id} sent event ControllerShortcut with packet {packet}");
+                    node.stats.shortcutted.add(&packet.pack_type);
                     node.shortcutted.push_front(packet);
-                    if let Window::Detail { tab: 2 } = self.screen.window {
+                    if let (Window::Detail { tab: 2 }, None) =
+                        (&self.screen.window, &self.history_page)
+                    {
                         self.packet_table_state.scroll_down_by(1);
                     };
 
-                    if node.shortcutted.len() > self.max_eventbuffer_dim {
-                        node.shortcutted.pop_back();
+                    if node.shortcutted.len() > self.history.retention.shortcutted {
+                        if let Some(old) = node.shortcutted.pop_back() {
+                            spill_or_log(&mut self.history, id, HistoryTable::Shortcutted, &old);
+                        }
                     }
                 }
             }
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use log::{error, trace};
+use serde::Serialize;
+use wg_2024::network::NodeId;
+
+use crate::network::history::{History, HistoryTable, Retention};
+
+/// writes an event removed from memory to the history, a failure is logged and the event is lost
+pub(crate) fn spill_or_log<T: Serialize>(
+    history: &mut History,
+    node: NodeId,
+    table: HistoryTable,
+    event: &T,
+) {
+    if let Err(e) = history.spill(node, table, event) {
+        error!("could not write event of #{node} to the history: {e}");
+    }
+}
+
+impl crate::MySimulationController {
+    /// writes the events that do not fit in memory anymore to `dir`, with one log per node and
+    /// table, so that the detail window can page back through them
+    pub fn set_history_dir(&mut self, dir: impl Into<PathBuf>) -> Result<(), String> {
+        self.history_page = None;
+        self.history.set_dir(dir)
+    }
+
+    /// sets how many events of each category are kept in memory for each node
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.history.retention = retention;
+    }
+
+    /// shows the next older or newer page of the table `tab` of the focused node, page 0 is the
+    /// one kept in memory
+    pub(crate) fn change_history_page(&mut self, tab: usize, older: bool) {
+        let id = self.screen.focus;
+        let Some(table) = HistoryTable::of(self.screen.kind, tab) else {
+            return;
+        };
+        let current = self.history_page.as_ref().map_or(0, |p| p.page);
+        let page = if older {
+            current + 1
+        } else {
+            current.saturating_sub(1)
+        };
+        if page == current || page > self.history.pages(id, table) {
+            return;
+        }
+        if page == 0 {
+            self.history_page = None;
+        } else {
+            match self.history.load(id, table, page) {
+                Ok(history_page) => self.history_page = Some(history_page),
+                Err(e) => {
+                    error!("could not read page {page} of the history of #{id}: {e}");
+                    return;
+                }
+            }
+        }
+        trace!("On window Detail of #{id}, showing history page {page}");
+        self.packet_table_state.select_first();
+    }
+}
@@ -1,35 +1,13 @@
 use crate::network::{self};
+use crate::screen::Window;
 use crossbeam_channel::unbounded;
-use log::debug;
-use network::{node_kind::NodeKind, node_representation::NodeRepresentation};
-use std::{
-    collections::{HashMap, HashSet},
-    thread::Builder,
-};
+use log::{debug, error, info};
+use network::node_kind::NodeKind;
+use std::{collections::HashMap, thread::Builder, time::Instant};
 
 use wg_2024::{controller::DroneCommand, drone::Drone, network::NodeId, packet::Packet};
 
 impl crate::MySimulationController {
-    /// sends the given packet directly to its final destination
-    /// Panics
-    /// - when the packet has no destination
-    /// - if there is no packet sender for the destination node
-    pub(crate) fn shortcut_packet(&mut self, mut packet: Packet) {
-        let dst = packet
-            .routing_header
-            .destination()
-            .unwrap_or_else(|| panic!("Destination for packet {packet} not found"));
-        if !packet.routing_header.hops.is_empty() {
-            packet.routing_header.hop_index = packet.routing_header.hops.len() - 1;
-        }
-        let sender = self
-            .packet_send
-            .get(&dst)
-            .unwrap_or_else(|| panic!("packet sender for #{dst} not found"));
-        debug!("Shortcutted packet: {packet}");
-        let _ = sender.send(packet);
-    }
-
     /// adds a connection between two nodes, first checking that the given source and destination
     /// follow certain rules(connections does not exist, at least one is a drone, not between same
     /// node,none of them crashed), then sends to the corresponding nodes in the simulation the command to add a
@@ -80,59 +58,129 @@ impl crate::MySimulationController {
     /// the sc
     /// # Panics
     /// - if there is no noderepresentation in the network for the crashing drone
-    /// - if the id is not of a drone
     /// - if there is no command sender for the drone or any of its neighbors
     /// - if there is no packet sender for the drone
     pub(crate) fn crash(&mut self, id: NodeId) -> Result<(), &'static str> {
-        // check that drone can be removed
-        self.network.crash_drone(id)?;
+        self.crash_drones(&[id])
+    }
+
+    /// crashes all the given drones, the network is checked to be valid with all of them crashed
+    /// before sending any command, so either all of them crash or none of them does
+    /// # Panics
+    /// same as `crash`
+    pub(crate) fn crash_drones(&mut self, ids: &[NodeId]) -> Result<(), &'static str> {
+        // check that drones can be removed
+        self.network.crash_drones(ids)?;
 
-        if let Some(drone_command_sender) = self.command_send.get(&id) {
+        for id in ids {
+            let id = *id;
+            let Some(drone_command_sender) = self.command_send.get(&id) else {
+                panic!("could not find command sender for drone #{id}")
+            };
             // send command to corresponding drone to crash
             let _ = drone_command_sender.send(DroneCommand::Crash);
-            let node = self
-                .network
-                .get_node_from_id(id)
-                .expect("could not find noderepresentation for drone {id}");
-            if !matches!(node.kind, NodeKind::Drone { .. }) {
-                unreachable!("trying to crash non-drone node #{id}")
-            }
-            let nodes = node.adj.clone();
-            for n in nodes {
-                let sender = self
-                    .command_send
-                    .get(&n)
-                    .expect("could not find comm sender for drone {n}");
-
-                // send command to neighbor drones to remove sender
-                let _ = sender.send(DroneCommand::RemoveSender(id));
-
-                if let Some(x) = self.network.get_mut_node_from_id(id) {
-                    x.adj.remove(&n);
-                }
-                if let Some(x) = self.network.get_mut_node_from_id(n) {
-                    x.adj.remove(&id);
+            // the network already removed the edges, the neighbors are the ones it had before
+            let neighbors: Vec<NodeId> = self.network.graph().previous_neighbors(id).collect();
+            for n in neighbors {
+                // send command to neighbor drones to remove sender, unless they are crashing too
+                if !ids.contains(&n) {
+                    let sender = self
+                        .command_send
+                        .get(&n)
+                        .expect("could not find comm sender for drone {n}");
+                    let _ = sender.send(DroneCommand::RemoveSender(id));
                 }
             }
             // remove the sender kept by the sc
             self.packet_send
                 .remove(&id)
                 .expect("could not find packet sender for drone {id}");
+        }
+        Ok(())
+    }
+
+    /// marks the node as failed after its thread exited, tells its neighbors to remove it and
+    /// forgets its packet sender, the rest of the simulation keeps running
+    pub(crate) fn node_failed(&mut self, id: NodeId, reason: String) {
+        error!("Node #{id} failed: {reason}");
+        let neighbors = self.network.fail_node(id, reason);
+        for n in neighbors {
+            // only drones can be told to remove a neighbor
+            if let Some(sender) = self.command_send.get(&n) {
+                let _ = sender.send(DroneCommand::RemoveSender(id));
+            }
+        }
+        self.packet_send.remove(&id);
+        self.screen.marked.remove(&id);
+        if self.screen.focus == id
+            && matches!(self.screen.window, Window::ChangePdr { batch: false, .. })
+        {
+            self.screen.window = Window::Error {
+                message: "the drone failed while its pdr was being changed",
+            };
+        }
+        self.refresh_focus();
+    }
 
-            Ok(())
-        } else {
-            panic!("could not find command sender for drone #{id}")
+    /// connects `from` to all the targets, checking that the resulting network is valid before
+    /// sending the commands to add the neighbors, so either all of them are added or none is
+    /// # Panics
+    /// when it cannot find the command or packet senders of the nodes
+    pub(crate) fn add_connections(
+        &mut self,
+        from: NodeId,
+        targets: &[NodeId],
+    ) -> Result<(), &'static str> {
+        self.network.add_edges(from, targets)?;
+        for to in targets {
+            if let (
+                Some(command_sender_from),
+                Some(command_sender_to),
+                Some(packet_sender_to),
+                Some(packet_sender_from),
+            ) = (
+                self.command_send.get(&from),
+                self.command_send.get(to),
+                self.packet_send.get(to),
+                self.packet_send.get(&from),
+            ) {
+                let _ = command_sender_from
+                    .send(DroneCommand::AddSender(*to, packet_sender_to.clone()));
+                let _ = command_sender_to
+                    .send(DroneCommand::AddSender(from, packet_sender_from.clone()));
+            } else {
+                unreachable!("could not find command senders or packet senders for nodes with id {from} and {to}");
+            }
+        }
+        Ok(())
+    }
+
+    /// changes the pdr of all the given drones, checking first that all of them are drones that
+    /// have not crashed
+    pub(crate) fn change_pdrs(&mut self, ids: &[NodeId], pdr: f32) -> Result<(), &'static str> {
+        let all_alive_drones = ids.iter().all(|id| {
+            matches!(
+                self.network.get_node_from_id(*id).map(|n| n.kind),
+                Some(NodeKind::Drone { crashed: false, .. })
+            )
+        });
+        if !all_alive_drones {
+            return Err("the pdr can only be changed for drones that have not crashed");
         }
+        for id in ids {
+            self.change_pdr(*id, pdr);
+        }
+        Ok(())
     }
 
     /// changes pdr of drone in representation and of actual drone
     /// # Panics
     /// panics if it can't find drone in the network, if it's not a drone, if it's crashed, if
     /// there is no command sender for it
-    pub(crate) fn change_pdr(&mut self, newpdr: f32) {
+    pub(crate) fn change_pdr(&mut self, id: NodeId, newpdr: f32) {
         let node = self
             .network
-            .get_mut_node_from_id(self.screen.focus)
+            .get_mut_node_from_id(id)
             .expect("could not find drone with matching id");
         match node.kind {
             NodeKind::Drone {
@@ -141,6 +189,7 @@ impl crate::MySimulationController {
             } => {
                 // change pdr of simulation drone
                 *pdr = newpdr;
+                node.drop_window.pdr_changed(Instant::now());
 
                 // change pdr of actual drone
                 let command_send = self
@@ -151,49 +200,115 @@ impl crate::MySimulationController {
             }
             _ => unreachable!("either not drone or crashed"),
         }
+        self.refresh_focus();
     }
 
-    /// creates drone with random id, spawns its thread, and adds it to the SC
+    /// adds to the network a drone with the given id connected to the given neighbors, spawns its
+    /// thread, tells the neighbors to add it, and selects it
     /// # Panics
-    /// panics if it can't create the drone thread
-    pub(crate) fn spawn_drone(&mut self) {
-        let kind = NodeKind::Drone {
-            pdr: 0.05,
-            crashed: false,
+    /// panics if it can't create the drone thread or if it can't find the senders of a neighbor
+    pub(crate) fn spawn_drone(
+        &mut self,
+        id: NodeId,
+        pdr: f32,
+        neighbors: &[NodeId],
+    ) -> Result<(), &'static str> {
+        self.network.add_drone(id, pdr, neighbors)?;
+        self.spawn_drone_thread(id, pdr, neighbors);
+        self.select_node(id);
+        Ok(())
+    }
+
+    /// brings back a crashed or failed drone under the same id, with a new SkyLink thread,
+    /// connected to the given neighbors or, if `None`, to the neighbors it had before that are
+    /// still alive
+    /// # Panics
+    /// same as `spawn_drone`
+    pub(crate) fn revive_drone(
+        &mut self,
+        id: NodeId,
+        neighbors: Option<&[NodeId]>,
+    ) -> Result<(), &'static str> {
+        let Some(node) = self.network.get_node_from_id(id) else {
+            return Err("cannot revive a node that is not in the network");
+        };
+        let NodeKind::Drone { pdr, .. } = node.kind else {
+            return Err("only crashed drones can be revived");
         };
-        let id = self.random_unique_id();
+        let neighbors: Vec<NodeId> = match neighbors {
+            Some(n) => n.to_vec(),
+            None => {
+                let mut previous: Vec<NodeId> = self
+                    .network
+                    .graph()
+                    .previous_neighbors(id)
+                    .filter(|n| {
+                        self.network.get_node_from_id(*n).is_some_and(|n| {
+                            n.failure.is_none()
+                                && !matches!(n.kind, NodeKind::Drone { crashed: true, .. })
+                        })
+                    })
+                    .collect();
+                previous.sort_unstable();
+                previous
+            }
+        };
+        self.network.revive_drone(id, &neighbors)?;
+        info!("reviving drone #{id} connected to {neighbors:?}");
+        self.spawn_drone_thread(id, pdr, &neighbors);
+        self.select_node(id);
+        Ok(())
+    }
+
+    /// spawns the SkyLink thread of a drone already in the network, registers its senders and
+    /// tells the neighbors to add it
+    /// # Panics
+    /// panics if it can't create the drone thread or if it can't find the senders of a neighbor
+    fn spawn_drone_thread(&mut self, id: NodeId, pdr: f32, neighbors: &[NodeId]) {
         let name = format!("SkyLink#{id}");
-        let mut n = NodeRepresentation::new(id, 0, 0, kind, HashSet::new());
-        n.thread_name.clone_from(&name);
+        if let Some(n) = self.network.get_mut_node_from_id(id) {
+            n.thread_name.clone_from(&name);
+        }
 
-        let event_send = self.droneevent_send.clone();
+        let event_send = self
+            .droneevent_send
+            .clone()
+            .unwrap_or_else(|| unreachable!("no drone is spawned after shutdown"));
         let (command_send, command_recv) = unbounded::<DroneCommand>();
         let (packet_send, packet_recv) = unbounded::<Packet>();
 
-        self.command_send.insert(n.id, command_send);
-        self.packet_send.insert(n.id, packet_send);
+        let mut neighbor_senders = HashMap::new();
+        for n in neighbors {
+            let sender = self
+                .packet_send
+                .get(n)
+                .unwrap_or_else(|| unreachable!("could not find packet sender for #{n}"));
+            neighbor_senders.insert(*n, sender.clone());
+            let _ = self
+                .command_send
+                .get(n)
+                .unwrap_or_else(|| unreachable!("could not find command sender for #{n}"))
+                .send(DroneCommand::AddSender(id, packet_send.clone()));
+        }
+
+        self.command_send.insert(id, command_send);
+        self.packet_send.insert(id, packet_send);
 
         let handle = Builder::new()
             .name(name)
             .spawn(move || {
                 skylink::SkyLinkDrone::new(
-                    n.id,
+                    id,
                     event_send,
                     command_recv,
                     packet_recv,
-                    HashMap::new(),
-                    0.05,
+                    neighbor_senders,
+                    pdr,
                 )
                 .run();
             })
             .expect("could not spawn drone thread");
 
-        self.node_handles.insert(n.id, handle);
-
-        self.network.nodes.push(n.clone());
-
-        self.node_list_state.select_last();
-        self.screen.focus = n.id;
-        self.screen.kind = kind;
+        self.node_handles.insert(id, handle);
     }
 }
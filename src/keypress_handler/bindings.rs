@@ -0,0 +1,620 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use crate::network::node_kind::NodeKind;
+use crate::screen::{Screen, Window};
+use crate::utilities::app_message::AppMessage;
+
+/// group of keys that share the same hint in the footer and the same description in the help
+/// overlay, each key is mapped to the `AppMessage` it produces
+///
+/// these tables are the only place where keys are defined: the keypress handler, the footer and
+/// the help overlay are all generated from them
+pub struct KeyBinding {
+    pub keys: &'static [(KeyCode, AppMessage)],
+    /// how the keys are displayed, e.g. "↑/↓"
+    pub label: &'static str,
+    /// short text shown in the footer
+    pub hint: &'static str,
+    /// longer explanation shown in the help overlay
+    pub description: &'static str,
+}
+
+impl KeyBinding {
+    /// returns the message associated to the pressed key, modifiers are ignored
+    fn message_for(&self, key: &KeyEvent) -> Option<AppMessage> {
+        self.keys
+            .iter()
+            .find(|(code, _)| *code == key.code)
+            .map(|(_, message)| message.clone())
+    }
+}
+
+const SCROLL_LIST: KeyBinding = KeyBinding {
+    keys: &[
+        (KeyCode::Up, AppMessage::ScrollUp),
+        (KeyCode::Down, AppMessage::ScrollDown),
+    ],
+    label: "↑/↓",
+    hint: "Scroll list",
+    description:
+        "Select the previous/next node of the list, the selected node is highlighted on the canvas",
+};
+
+const HELP: KeyBinding = KeyBinding {
+    keys: &[
+        (KeyCode::Char('?'), AppMessage::ToggleHelp),
+        (KeyCode::F(1), AppMessage::ToggleHelp),
+    ],
+    label: "?/F1",
+    hint: "Help",
+    description: "Show every key available in the current window",
+};
+
+const QUIT: KeyBinding = KeyBinding {
+    keys: &[(KeyCode::Char('q'), AppMessage::Quit)],
+    label: "q",
+    hint: "Quit",
+    description: "Exit the simulation controller",
+};
+
+pub const MAIN_BINDINGS: [KeyBinding; 15] = [
+    SCROLL_LIST,
+    KeyBinding {
+        keys: &[(KeyCode::Char(' '), AppMessage::ToggleMark)],
+        label: "space",
+        hint: "Mark",
+        description: "Mark/unmark the selected node, marked nodes can be changed all at once",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('m'), AppMessage::WindowMove)],
+        label: "m",
+        hint: "Move node",
+        description: "Move the selected node around the canvas with the arrow keys",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('c'), AppMessage::WindowAddConnection)],
+        label: "c",
+        hint: "Add connection",
+        description: "Start adding a connection from the selected node, then pick the other end from the list",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('+'), AppMessage::SpawnDrone)],
+        label: "+",
+        hint: "Spawn node",
+        description: "Spawn a new SkyLink drone with a unique id and select it",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('d'), AppMessage::WindowDetail)],
+        label: "d",
+        hint: "Detail view",
+        description: "Open the packets and messages sent, dropped or received by the selected node",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('g'), AppMessage::WindowMetrics)],
+        label: "g",
+        hint: "Metrics",
+        description: "Open the charts of fragments, acks, nacks, floods and messages over time",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('r'), AppMessage::WindowReportCard)],
+        label: "r",
+        hint: "Report card",
+        description: "Open the protocol violations of the drones, grouped by implementation",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('f'), AppMessage::WindowFloods)],
+        label: "f",
+        hint: "Floods",
+        description: "Open the floods, each drawn as the tree along which its requests spread and the paths of its responses",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('n'), AppMessage::WindowNacks)],
+        label: "n",
+        hint: "Nacks",
+        description: "Open the nacks counted by type, grouped by the node that created them or by session, with the packets of the selected group",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('i'), AppMessage::WindowDiagnostics)],
+        label: "i",
+        hint: "Incidents",
+        description: "Open the malformed events that the controller received and ignored",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char(':'), AppMessage::WindowCommand)],
+        label: ":",
+        hint: "Command",
+        description: "Open the command prompt: crash <id>, pdr <id> <pdr>, connect <id> <id>, spawn <id> [pdr=<pdr>] [neighbors=<id>,..], revive <id> [neighbors=<id>,..], select <id>, sweep <id> ramp|steps|stop .., chaos <seed>|stop, shortcut immediate|delay <secs>|drop|reroute, export topology <file>, export stats [dir]",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('e'), AppMessage::ExportStats)],
+        label: "e",
+        hint: "Export stats",
+        description: "Write the statistics of every node and edge as csv and json in the statistics directory",
+    },
+    HELP,
+    QUIT,
+];
+
+/// available in the main window only when at least one node is marked
+pub const MARKED_BINDINGS: [KeyBinding; 4] = [
+    KeyBinding {
+        keys: &[(KeyCode::Char('P'), AppMessage::WindowChangePDRMarked)],
+        label: "P",
+        hint: "PDR of marked",
+        description: "Change the packet drop rate of all the marked drones at once",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('K'), AppMessage::CrashMarked)],
+        label: "K",
+        hint: "Crash marked",
+        description: "Crash all the marked drones, only if the network stays valid once all of them crashed, otherwise none of them is crashed",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('C'), AppMessage::ConnectMarked)],
+        label: "C",
+        hint: "Connect to marked",
+        description: "Connect the selected node to all the marked nodes, if any of the connections is not valid none of them is added",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('u'), AppMessage::ClearMarks)],
+        label: "u",
+        hint: "Unmark all",
+        description: "Unmark all the marked nodes",
+    },
+];
+
+pub const ERROR_BINDINGS: [KeyBinding; 3] = [
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Ok",
+        description: "Close the error and go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const ADD_CONNECTION_BINDINGS: [KeyBinding; 4] = [
+    SCROLL_LIST,
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Connect to selected node",
+        description: "Connect the origin node to the selected node, the network must stay valid",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const MOVE_BINDINGS: [KeyBinding; 4] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::MoveNode { x: 0, y: 1 }),
+            (KeyCode::Down, AppMessage::MoveNode { x: 0, y: -1 }),
+            (KeyCode::Left, AppMessage::MoveNode { x: -1, y: 0 }),
+            (KeyCode::Right, AppMessage::MoveNode { x: 1, y: 0 }),
+        ],
+        label: "↑/↓/→/←",
+        hint: "Move",
+        description: "Move the selected node one step in the given direction",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Ok",
+        description: "Stop moving the node and go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const DETAIL_BINDINGS: [KeyBinding; 5] = [
+    SCROLL_LIST,
+    KeyBinding {
+        keys: &[(KeyCode::Tab, AppMessage::ChangeTab)],
+        label: "tab",
+        hint: "Next list",
+        description: "Switch to the next table of packets or messages",
+    },
+    KeyBinding {
+        keys: &[
+            (KeyCode::PageUp, AppMessage::OlderPage),
+            (KeyCode::PageDown, AppMessage::NewerPage),
+        ],
+        label: "PgUp/PgDn",
+        hint: "History",
+        description: "Page back through the older events written to the history directory, or forward to the ones kept in memory",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('o'), AppMessage::WindowRoute)],
+        label: "o",
+        hint: "Follow route",
+        description: "Draw the source route of the selected packet, or of the last packet of the selected message, on the network",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the main window",
+    },
+];
+
+pub const DETAIL_DRONE_BINDINGS: [KeyBinding; 3] = [
+    KeyBinding {
+        keys: &[(KeyCode::Char('p'), AppMessage::WindowChangePDR)],
+        label: "p",
+        hint: "Edit PDR",
+        description: "Change the packet drop rate of the drone, not available once it crashed",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('k'), AppMessage::Crash)],
+        label: "k",
+        hint: "Crash",
+        description:
+            "Crash the drone and remove it from its neighbors, only if the network stays valid",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Char('v'), AppMessage::Revive)],
+        label: "v",
+        hint: "Revive",
+        description: "Start a new SkyLink drone with the same id and pdr of the crashed or failed drone, connected to its previous neighbors that are still alive",
+    },
+];
+
+pub const METRICS_BINDINGS: [KeyBinding; 5] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::ScrollUp),
+            (KeyCode::Down, AppMessage::ScrollDown),
+        ],
+        label: "↑/↓",
+        hint: "Select node",
+        description: "Select the previous/next node of the list, its charts are shown in the per node breakdown",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Tab, AppMessage::ChangeTab)],
+        label: "tab",
+        hint: "Network/node",
+        description: "Switch between the traffic of the whole network and the one of the selected node",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const DIAGNOSTICS_BINDINGS: [KeyBinding; 4] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::ScrollUp),
+            (KeyCode::Down, AppMessage::ScrollDown),
+        ],
+        label: "↑/↓",
+        hint: "Select incident",
+        description: "Select the previous/next incident, its event is shown below the list",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const FLOODS_BINDINGS: [KeyBinding; 4] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::ScrollUp),
+            (KeyCode::Down, AppMessage::ScrollDown),
+        ],
+        label: "↑/↓",
+        hint: "Select flood",
+        description:
+            "Select the previous/next flood, newest first, its tree is drawn on the canvas",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const NACKS_BINDINGS: [KeyBinding; 5] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::ScrollUp),
+            (KeyCode::Down, AppMessage::ScrollDown),
+        ],
+        label: "↑/↓",
+        hint: "Select group",
+        description:
+            "Select the previous/next node or session, its nacks are listed below the table",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Tab, AppMessage::ChangeTab)],
+        label: "tab",
+        hint: "Node/session",
+        description:
+            "Switch between grouping the nacks by the node that created them and by session",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const ROUTE_BINDINGS: [KeyBinding; 4] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Left, AppMessage::PreviousHop),
+            (KeyCode::Right, AppMessage::NextHop),
+        ],
+        label: "←/→",
+        hint: "Step",
+        description: "Move one hop back/forward along the route, the hops up to the current one are highlighted",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the detail window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const REPORT_CARD_BINDINGS: [KeyBinding; 3] = [
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Go back to the main window",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const PDR_BINDINGS: [KeyBinding; 6] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::ScrollUp),
+            (KeyCode::Down, AppMessage::ScrollDown),
+        ],
+        label: "↑/↓",
+        hint: "Increase/decrease PDR",
+        description: "Increase/decrease the packet drop rate by 0.01, discarding the typed value",
+    },
+    KeyBinding {
+        keys: &[],
+        label: "0-9 .",
+        hint: "Type PDR",
+        description: "Type the exact packet drop rate, a number between 0 and 1",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Backspace, AppMessage::DeleteChar)],
+        label: "Backspace",
+        hint: "Delete",
+        description: "Delete the last digit of the typed packet drop rate",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Done",
+        description: "Send the new packet drop rate to the drone, if the typed one is not valid the error is shown instead",
+    },
+    HELP,
+    QUIT,
+];
+
+pub const COMMAND_BINDINGS: [KeyBinding; 7] = [
+    KeyBinding {
+        keys: &[],
+        label: "a-z 0-9",
+        hint: "Type",
+        description: "Type the command, every key that is not listed here is added to it",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Backspace, AppMessage::DeleteChar)],
+        label: "Backspace",
+        hint: "Delete",
+        description: "Delete the last character of the command",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Tab, AppMessage::Complete)],
+        label: "tab",
+        hint: "Complete",
+        description: "Complete the command name or the node id being typed",
+    },
+    KeyBinding {
+        keys: &[
+            (KeyCode::Up, AppMessage::ScrollUp),
+            (KeyCode::Down, AppMessage::ScrollDown),
+        ],
+        label: "↑/↓",
+        hint: "History",
+        description: "Browse the commands that were run before",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Enter, AppMessage::Done)],
+        label: "Enter",
+        hint: "Run",
+        description: "Run the command, if it fails the error is shown below it",
+    },
+    KeyBinding {
+        keys: &[(KeyCode::Esc, AppMessage::Cancel)],
+        label: "Esc",
+        hint: "Cancel",
+        description: "Close the command prompt without running anything",
+    },
+    // `?` is typed in the command, so only F1 shows the help
+    KeyBinding {
+        keys: &[(KeyCode::F(1), AppMessage::ToggleHelp)],
+        label: "F1",
+        hint: "Help",
+        description: "Show every key available in the current window",
+    },
+];
+
+pub const HELP_BINDINGS: [KeyBinding; 2] = [
+    KeyBinding {
+        keys: &[
+            (KeyCode::Char('?'), AppMessage::ToggleHelp),
+            (KeyCode::F(1), AppMessage::ToggleHelp),
+            (KeyCode::Esc, AppMessage::ToggleHelp),
+            (KeyCode::Enter, AppMessage::ToggleHelp),
+        ],
+        label: "?/F1/Esc/Enter",
+        hint: "Close help",
+        description: "Close the help overlay",
+    },
+    QUIT,
+];
+
+/// returns the bindings of the current window, taking into account the kind of the selected node
+/// and the marked nodes
+pub fn window_bindings(screen: &Screen) -> Vec<&'static KeyBinding> {
+    match screen.window {
+        Window::Main => {
+            let mut bindings: Vec<&KeyBinding> = MAIN_BINDINGS.iter().collect();
+            if !screen.marked.is_empty() {
+                // keep help and quit last
+                let tail = bindings.split_off(bindings.len() - 2);
+                bindings.extend(MARKED_BINDINGS.iter());
+                bindings.extend(tail);
+            }
+            bindings
+        }
+        Window::Move => MOVE_BINDINGS.iter().collect(),
+        Window::AddConnection { .. } => ADD_CONNECTION_BINDINGS.iter().collect(),
+        Window::ChangePdr { .. } => PDR_BINDINGS.iter().collect(),
+        Window::Error { .. } => ERROR_BINDINGS.iter().collect(),
+        Window::Metrics { .. } => METRICS_BINDINGS.iter().collect(),
+        Window::ReportCard => REPORT_CARD_BINDINGS.iter().collect(),
+        Window::Diagnostics => DIAGNOSTICS_BINDINGS.iter().collect(),
+        Window::Floods => FLOODS_BINDINGS.iter().collect(),
+        Window::Nacks { .. } => NACKS_BINDINGS.iter().collect(),
+        Window::Route { .. } => ROUTE_BINDINGS.iter().collect(),
+        Window::Command { .. } => COMMAND_BINDINGS.iter().collect(),
+        Window::Detail { .. } => {
+            let mut bindings: Vec<&KeyBinding> = DETAIL_BINDINGS.iter().collect();
+            if let NodeKind::Drone { .. } = screen.kind {
+                bindings.extend(DETAIL_DRONE_BINDINGS.iter());
+            }
+            bindings.extend([&HELP, &QUIT]);
+            bindings
+        }
+    }
+}
+
+/// returns the bindings that are currently active, which are the ones of the help overlay when
+/// it is shown
+pub fn active_bindings(screen: &Screen) -> Vec<&'static KeyBinding> {
+    if screen.help {
+        HELP_BINDINGS.iter().collect()
+    } else {
+        window_bindings(screen)
+    }
+}
+
+/// returns the message of the first active binding that matches the pressed key, in windows
+/// that accept text any other character is typed
+pub fn message_for(screen: &Screen, key: &KeyEvent) -> Option<AppMessage> {
+    let message = active_bindings(screen)
+        .iter()
+        .find_map(|binding| binding.message_for(key));
+    match (message, key.code) {
+        (None, KeyCode::Char(c)) if !screen.help && screen.window.accepts_text() => {
+            Some(AppMessage::InputChar(c))
+        }
+        (message, _) => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use super::*;
+    use crate::network::nacks::NackGrouping;
+
+    fn all_windows() -> Vec<Window> {
+        vec![
+            Window::Main,
+            Window::Move,
+            Window::AddConnection { origin: 0 },
+            Window::ChangePdr {
+                pdr: 0.0,
+                batch: false,
+                input: String::new(),
+                error: None,
+            },
+            Window::Error { message: "" },
+            Window::Detail { tab: 0 },
+            Window::Metrics { per_node: true },
+            Window::ReportCard,
+            Window::Diagnostics,
+            Window::Floods,
+            Window::Nacks {
+                grouping: NackGrouping::Emitter,
+            },
+            Window::Route {
+                tab: 0,
+                hops: Vec::new(),
+                hop_index: 0,
+                step: 0,
+            },
+            Window::Command {
+                input: String::new(),
+                history: None,
+                error: None,
+                candidates: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn no_key_is_bound_twice() {
+        let kinds = [
+            NodeKind::Drone {
+                pdr: 0.0,
+                crashed: false,
+            },
+            NodeKind::Client,
+            NodeKind::Server,
+        ];
+        for kind in kinds {
+            for window in all_windows() {
+                let screen = Screen {
+                    focus: 1,
+                    kind,
+                    window,
+                    help: false,
+                    marked: BTreeSet::from([1]),
+                };
+                let mut seen = HashSet::new();
+                for binding in window_bindings(&screen) {
+                    for (code, _) in binding.keys {
+                        assert!(
+                            seen.insert(*code),
+                            "{code:?} is bound twice in {:?} for {kind:?}",
+                            screen.window
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
 
 // graphics, keypress handling and helpers
+mod command;
 mod keypress_handler;
 mod network;
 mod screen;
@@ -8,19 +9,37 @@ mod utilities;
 mod view;
 
 // impls for MySimulationController
+mod chaos;
+mod diagnostics;
 mod event_saver;
+mod history;
 mod interacts_with_simulation;
+mod metrics_file;
+mod pdr_sweep;
+mod pipeline;
+mod shortcut;
+mod shutdown;
+mod stats_export;
 mod transition;
 
 #[cfg(feature = "custom_terminal_backend")]
 use ratatui::backend::TestBackend;
 
+use crate::chaos::Chaos;
+use crate::diagnostics::{panic_message, Incident};
+use crate::metrics_file::MetricsFile;
+use crate::network::history::{History, HistoryPage};
 use crate::network::Network;
+use crate::pdr_sweep::PdrSweep;
+use crate::pipeline::{FrameClock, Hud, FRAME_INTERVAL};
 use crate::screen::Screen;
-use crossbeam_channel::{select, Receiver, Sender};
+use crate::shortcut::{DelayedShortcut, PolicyStats};
+use crate::shutdown::DEFAULT_SHUTDOWN_TIMEOUT;
+use crate::stats_export::DEFAULT_STATS_DIR;
+use crossbeam_channel::{Receiver, Sender};
 #[cfg(feature = "appmessage_through_crossbeam")]
 use crossterm::event::KeyEvent;
-use log::{debug, info};
+use log::{error, info};
 use messages::node_event::NodeEvent;
 use network::node_kind::NodeKind;
 use ratatui::{
@@ -28,7 +47,18 @@ use ratatui::{
     Terminal,
 };
 use screen::Window;
-use std::{collections::HashMap, thread::JoinHandle};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    path::PathBuf,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+pub use chaos::ChaosOptions;
+pub use network::history::{EventCategory, Retention};
+pub use network::reassembly::PayloadDecoder;
+pub use shortcut::ShortcutPolicy;
+pub use shutdown::ShutdownReport;
 
 use wg_2024::{
     config::Config,
@@ -45,6 +75,7 @@ pub struct SimControllerOptions {
     pub nodeevent_recv: Receiver<NodeEvent>,
     pub node_handles: HashMap<NodeId, JoinHandle<()>>,
     pub config: Config,
+    /// events of each category kept in memory for each node, until changed with `set_retention`
     pub max_eventbuffer_dim: usize,
 }
 
@@ -55,7 +86,8 @@ pub struct MySimulationController {
     packet_send: HashMap<NodeId, Sender<Packet>>,
     command_send: HashMap<NodeId, Sender<DroneCommand>>,
     nodeevent_recv: Receiver<NodeEvent>,
-    droneevent_send: Sender<DroneEvent>,
+    /// cloned for the spawned drones, dropped on shutdown like the other senders
+    droneevent_send: Option<Sender<DroneEvent>>,
     droneevent_recv: Receiver<DroneEvent>,
     node_handles: HashMap<NodeId, JoinHandle<()>>,
     // internal state
@@ -64,6 +96,30 @@ pub struct MySimulationController {
     node_list_state: ListState,
     packet_table_state: TableState,
     screen: Screen,
+    command_history: Vec<String>,
+    pdr_sweeps: Vec<PdrSweep>,
+    chaos: Option<Chaos>,
+    shortcut_policy: ShortcutPolicy,
+    /// packets held back by the delayed shortcut policy, oldest first
+    delayed_shortcuts: VecDeque<DelayedShortcut>,
+    shortcut_stats: BTreeMap<&'static str, PolicyStats>,
+    stats_dir: Option<PathBuf>,
+    metrics_file: Option<MetricsFile>,
+    /// drone and node events received since the start
+    events_ingested: u64,
+    /// malformed events, newest first
+    incidents: VecDeque<Incident>,
+    /// panic on malformed events instead of recording them
+    strict: bool,
+    /// how long the node threads are waited for on quit
+    shutdown_timeout: Duration,
+    shutdown_report: Option<ShutdownReport>,
+    /// decodes the payload of the sessions whose fragments were all sent
+    payload_decoder: Option<PayloadDecoder>,
+    /// events that do not fit in memory anymore
+    history: History,
+    /// older events shown in the detail window instead of the ones in memory
+    history_page: Option<HistoryPage>,
     max_eventbuffer_dim: usize,
 }
 
@@ -99,7 +155,7 @@ impl MySimulationController {
             keyevent_recv: None,
             command_send: opt.command_send,
             droneevent_recv: opt.droneevent_recv,
-            droneevent_send: opt.droneevent_send,
+            droneevent_send: Some(opt.droneevent_send),
             nodeevent_recv: opt.nodeevent_recv,
             packet_send: opt.packet_send,
             node_handles: opt.node_handles,
@@ -115,8 +171,26 @@ impl MySimulationController {
                     crashed: false,
                 },
                 window: Window::Main,
+                help: false,
+                marked: BTreeSet::new(),
             },
             running: true,
+            command_history: Vec::new(),
+            pdr_sweeps: Vec::new(),
+            chaos: None,
+            shortcut_policy: ShortcutPolicy::default(),
+            delayed_shortcuts: VecDeque::new(),
+            shortcut_stats: BTreeMap::new(),
+            stats_dir: None,
+            metrics_file: None,
+            events_ingested: 0,
+            incidents: VecDeque::new(),
+            strict: false,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            shutdown_report: None,
+            payload_decoder: None,
+            history: History::new(Retention::uniform(opt.max_eventbuffer_dim)),
+            history_page: None,
             max_eventbuffer_dim: opt.max_eventbuffer_dim,
         }
     }
@@ -128,14 +202,25 @@ impl MySimulationController {
     /// - unreachable panics are meant to never be reached during runtime, and if they are, they
     ///     are most likely sign of a bug in the internal workings of the SC, like not finding packet
     ///     senders or command senders for a given drone
-    /// - normal panics are instead used when the sc receives a NodeEvent/DroneEvent that can be
-    ///     considered 'broken', for example hop index out of bounds, a empty hops vector
+    /// - normal panics are instead used in strict mode when the sc receives a NodeEvent/DroneEvent
+    ///     that can be considered 'broken', for example hop index out of bounds, a empty hops
+    ///     vector, otherwise these events are recorded as incidents
     #[cfg_attr(coverage_nightly, coverage(off))]
     pub fn run(&mut self) {
         let terminal = ratatui::init();
         self.start(terminal);
         info!("sc exiting normally...");
         ratatui::restore();
+        // the terminal is back to normal, so the report can be read
+        if let Some(report) = self.shutdown_report.as_ref().filter(|r| !r.is_clean()) {
+            eprintln!("{report}");
+        }
+    }
+
+    /// sets how the payload of a reassembled session is turned back into a message, to compare it
+    /// with the message the source said it was sending, without a decoder only the latter is shown
+    pub fn set_payload_decoder(&mut self, decoder: PayloadDecoder) {
+        self.payload_decoder = Some(decoder);
     }
 
     #[cfg(feature = "custom_terminal_backend")]
@@ -150,51 +235,58 @@ impl MySimulationController {
 }
 
 impl MySimulationController {
-    /// runs the main loop of the sc
+    /// runs the main loop of the sc, a node thread that exits without being crashed marks the
+    /// node as failed
     /// Panics
-    /// - if a node thread exits and the node was not a crashing drone
+    /// - in strict mode, if a node thread exits and the node was not a crashing drone
     /// - when a channel receiver returns an error
     fn start<B: ratatui::backend::Backend>(&mut self, mut terminal: Terminal<B>) {
         info!("started SC");
         let mut finished: Vec<NodeId> = Vec::new();
+        let mut clock = FrameClock::new();
+        let mut hud = Hud::new(Instant::now());
         while self.running {
             // ---------------------------------------------------------------------------
-            // draw interface
+            // draw interface, only when something changed and at most once per frame interval
             // ---------------------------------------------------------------------------
-            terminal
-                .draw(|frame| {
-                    crate::view::render(
-                        &self.network,
-                        &self.screen,
-                        &mut self.node_list_state,
-                        &mut self.packet_table_state,
-                        frame,
-                    );
-                })
-                .expect("terminal.draw exited with error");
+            let now = Instant::now();
+            hud.update(now, self.backlog());
+            if clock.should_draw(now) {
+                terminal
+                    .draw(|frame| {
+                        crate::view::render(
+                            &self.network,
+                            &self.screen,
+                            &self.pdr_sweeps,
+                            self.chaos.as_ref(),
+                            &self.incidents,
+                            self.history_page.as_ref(),
+                            &hud,
+                            &mut self.node_list_state,
+                            &mut self.packet_table_state,
+                            frame,
+                        );
+                    })
+                    .expect("terminal.draw exited with error");
+                clock.drawn(now);
+                hud.record_frame();
+            }
 
             // ---------------------------------------------------------------------------
-            // listen for keypresses
+            // listen for keypresses, waiting for the next frame if there are no events
             // ---------------------------------------------------------------------------
-            #[cfg(feature = "appmessage_through_crossbeam")]
-            if let Some(rcv) = &self.keyevent_recv {
-                if let Some(message) =
-                    keypress_handler::handle_keypress_from_recv(&self.screen, rcv)
-                {
-                    debug!(
-                        "received AppMessage through crossbeam channel: {:?}",
-                        message
-                    );
-                    self.transition(&message);
-                };
-            };
+            if self.handle_input(clock.until_next_frame(Instant::now())) {
+                clock.mark_dirty();
+            }
 
-            #[cfg(not(feature = "appmessage_through_crossbeam"))]
-            #[cfg_attr(coverage_nightly, coverage(off))]
-            if let Some(message) = keypress_handler::handle_crossterm_events(&self.screen) {
-                debug!("received AppMessage: {:?}", message);
-                self.transition(&message);
-            };
+            // ---------------------------------------------------------------------------
+            // apply scheduled pdr changes and random faults, send delayed shortcuts, write
+            // metrics
+            // ---------------------------------------------------------------------------
+            self.run_pdr_sweeps();
+            self.run_chaos();
+            self.run_delayed_shortcuts();
+            self.write_metrics(false);
 
             // ---------------------------------------------------------------------------
             // check if node threads exit
@@ -206,68 +298,52 @@ impl MySimulationController {
             }
             while let Some(id) = finished.pop() {
                 let h = self.node_handles.remove(&id).unwrap();
-                let node = self
+                let kind = self
                     .network
                     .get_node_from_id(id)
-                    .expect("could not find node for node_handle of id #{id}");
-                let res = h.join();
-                match (res, node.kind) {
+                    .expect("could not find node for node_handle of id #{id}")
+                    .kind;
+                let reason = match (h.join(), kind) {
                     (
                         Ok(()),
                         NodeKind::Drone {
                             pdr: _,
                             crashed: true,
                         },
-                    ) => info!("Crashed drone #{id} exited successfully"),
-                    (Ok(()), _) => {
-                        panic!("Node #{id} unexpectedly exited thread successfully")
+                    ) => {
+                        info!("Crashed drone #{id} exited successfully");
+                        continue;
                     }
+                    (Ok(()), _) => "exited thread successfully".to_string(),
                     (Err(e), _) => {
-                        panic!(
-                            "Node #{id} unexpectedly exited thread with an error: {:?}",
-                            e.downcast_ref::<&str>()
-                                .copied()
-                                .or_else(|| e.downcast_ref::<String>().map(|x| x.as_str()))
-                        )
+                        format!("exited thread with an error: {}", panic_message(e.as_ref()))
                     }
+                };
+                if self.strict {
+                    panic!("Node #{id} unexpectedly {reason}");
                 }
+                self.node_failed(id, reason);
             }
 
             // ---------------------------------------------------------------------------
-            // go through all NodeEvents and DroneEvents
+            // go through the NodeEvents and DroneEvents, until the next frame is due
             // ---------------------------------------------------------------------------
-            loop {
-                select! {
-                                    recv(self.droneevent_recv)->res =>{
-                                        match res{
-                    Ok(event) => {
-
-                                            if let DroneEvent::ControllerShortcut(ref packet) = event {
-                                                self.shortcut_packet(packet.clone());
-                                            }
-                                            self.save_droneevent(event);
-                    },
-                    Err(err) => {
-                                           panic!("error for nodevent receiver: {err:?}");
-                        },
-                    }
-                                        },
-                                        recv(self.nodeevent_recv)-> res =>{
-                                        match res{
-                    Ok(event) => {
-
-                                            self.save_nodeevent(event);
-                    },
-                    Err(err) => {
-                                            panic!("error for nodevent receiver: {err:?}");
-                    },
-                }
-
-                                    }
-                                    default => break,
-                                }
+            let ingested = self.ingest_events(Instant::now() + FRAME_INTERVAL);
+            if ingested > 0 {
+                hud.record_events(ingested);
+                clock.mark_dirty();
             }
         }
+
+        self.write_metrics(true);
+        let dir = self
+            .stats_dir
+            .clone()
+            .unwrap_or_else(|| DEFAULT_STATS_DIR.into());
+        if let Err(e) = self.export_stats(&dir) {
+            error!("could not export statistics on quit: {e}");
+        }
+        self.shutdown();
     }
 
     /// generates a random id for a node, different from any of the other nodes in the network
@@ -297,6 +373,22 @@ impl MySimulationController {
         }
     }
 
+    /// selects the node with the given id in the list, then updates focus and kind accordingly
+    fn select_node(&mut self, id: NodeId) {
+        if let Some(pos) = self.network.nodes().position(id) {
+            self.node_list_state.select(Some(pos));
+            self.screen.focus = id;
+            self.screen.kind = self.network.nodes()[pos].kind;
+        }
+    }
+
+    /// updates the kind of the focused node, to be called after it has been changed
+    fn refresh_focus(&mut self) {
+        if let Some(node) = self.network.get_node_from_id(self.screen.focus) {
+            self.screen.kind = node.kind;
+        }
+    }
+
     /// resets list to first node, then updates focus and kind accordingly
     fn reset_list(&mut self) {
         self.node_list_state.select_first();
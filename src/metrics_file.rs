@@ -0,0 +1,224 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use log::{error, trace};
+
+use crate::network::node_kind::NodeKind;
+use crate::network::node_stats::{NodeStats, PacketCounts};
+use crate::network::Network;
+
+/// where and how often the metrics file is written
+pub struct MetricsFile {
+    pub path: PathBuf,
+    pub interval: Duration,
+    last_write: Instant,
+    /// events ingested when the file was last written, to compute the ingestion rate
+    last_events: u64,
+}
+
+/// selects one of the packet counters of a node
+type CountsOf = fn(&NodeStats) -> &PacketCounts;
+
+fn push_family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "# HELP {name} {help}");
+}
+
+fn push_packet_counts(out: &mut String, name: &str, drone: u8, counts: &PacketCounts) {
+    for (t, value) in [
+        ("fragment", counts.fragment),
+        ("ack", counts.ack),
+        ("nack", counts.nack),
+        ("flood_request", counts.flood_request),
+        ("flood_response", counts.flood_response),
+    ] {
+        let _ = writeln!(
+            out,
+            "{name}_total{{drone=\"{drone}\",type=\"{t}\"}} {value}"
+        );
+    }
+}
+
+/// renders the counters of the network in the OpenMetrics text format
+pub fn render_openmetrics(network: &Network, events_total: u64, events_per_sec: f64) -> String {
+    let mut nodes: Vec<_> = network.nodes().iter().collect();
+    nodes.sort_by_key(|n| n.id);
+    let drones: Vec<_> = nodes
+        .iter()
+        .filter(|n| matches!(n.kind, NodeKind::Drone { .. }))
+        .collect();
+    let endpoints: Vec<_> = nodes
+        .iter()
+        .filter(|n| matches!(n.kind, NodeKind::Client | NodeKind::Server))
+        .collect();
+
+    let drone_families: [(&str, &str, CountsOf); 3] = [
+        (
+            "sc_drone_forwarded_packets",
+            "Packets forwarded by the drone.",
+            |s| &s.sent,
+        ),
+        (
+            "sc_drone_dropped_packets",
+            "Packets dropped by the drone.",
+            |s| &s.dropped,
+        ),
+        (
+            "sc_drone_shortcutted_packets",
+            "Packets the drone sent through the controller shortcut.",
+            |s| &s.shortcutted,
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, help, counts) in drone_families {
+        push_family(&mut out, name, "counter", help);
+        for n in &drones {
+            push_packet_counts(&mut out, name, n.id, counts(&n.stats));
+        }
+    }
+
+    push_family(
+        &mut out,
+        "sc_sessions_started",
+        "counter",
+        "Messages the client or server started sending.",
+    );
+    for n in &endpoints {
+        let _ = writeln!(
+            out,
+            "sc_sessions_started_total{{node=\"{}\",kind=\"{}\"}} {}",
+            n.id,
+            kind_label(n.kind),
+            n.stats.messages_started.values().sum::<u64>()
+        );
+    }
+    push_family(
+        &mut out,
+        "sc_sessions_completed",
+        "counter",
+        "Messages the client or server finished sending.",
+    );
+    for n in &endpoints {
+        let _ = writeln!(
+            out,
+            "sc_sessions_completed_total{{node=\"{}\",kind=\"{}\"}} {}",
+            n.id,
+            kind_label(n.kind),
+            n.stats.messages_completed.values().sum::<u64>()
+        );
+    }
+
+    push_family(
+        &mut out,
+        "sc_events_ingested",
+        "counter",
+        "Drone and node events received by the controller.",
+    );
+    let _ = writeln!(out, "sc_events_ingested_total {events_total}");
+    push_family(
+        &mut out,
+        "sc_event_ingestion_rate",
+        "gauge",
+        "Events received per second since the previous snapshot.",
+    );
+    let _ = writeln!(out, "sc_event_ingestion_rate {events_per_sec}");
+    push_family(
+        &mut out,
+        "sc_crashed_drones",
+        "gauge",
+        "Drones that have crashed.",
+    );
+    let crashed = drones
+        .iter()
+        .filter(|n| matches!(n.kind, NodeKind::Drone { crashed: true, .. }))
+        .count();
+    let _ = writeln!(out, "sc_crashed_drones {crashed}");
+    out.push_str("# EOF\n");
+    out
+}
+
+fn kind_label(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Drone { .. } => "drone",
+        NodeKind::Client => "client",
+        NodeKind::Server => "server",
+    }
+}
+
+/// writes the content to a temporary file next to the path and then renames it, so that readers
+/// never see a partially written file
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+impl crate::MySimulationController {
+    /// writes the counters of the controller to `path` in the OpenMetrics text format every
+    /// `interval`, e.g. for the textfile collector of node-exporter
+    pub fn set_metrics_file(&mut self, path: impl Into<PathBuf>, interval: Duration) {
+        self.metrics_file = Some(MetricsFile {
+            path: path.into(),
+            interval,
+            last_write: Instant::now(),
+            last_events: self.events_ingested,
+        });
+    }
+
+    /// writes the metrics file if enough time passed since the last time, or always if `force`
+    pub(crate) fn write_metrics(&mut self, force: bool) {
+        let Some(file) = &mut self.metrics_file else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(file.last_write);
+        if !force && elapsed < file.interval {
+            return;
+        }
+        let rate = if elapsed.is_zero() {
+            0.0
+        } else {
+            (self.events_ingested - file.last_events) as f64 / elapsed.as_secs_f64()
+        };
+        let content = render_openmetrics(&self.network, self.events_ingested, rate);
+        match write_atomic(&file.path, &content) {
+            Ok(()) => trace!("written metrics to {}", file.path.display()),
+            Err(e) => error!("could not write metrics to {}: {e}", file.path.display()),
+        }
+        file.last_write = now;
+        file.last_events = self.events_ingested;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::tests::line_network;
+
+    #[test]
+    fn openmetrics_text() {
+        let mut network = line_network();
+        network.crash_drones(&[1]).unwrap();
+        network
+            .get_mut_node_from_id(2)
+            .unwrap()
+            .stats
+            .dropped
+            .fragment = 3;
+
+        let text = render_openmetrics(&network, 10, 2.5);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines.contains(&"sc_drone_dropped_packets_total{drone=\"2\",type=\"fragment\"} 3"));
+        assert!(lines.contains(&"sc_sessions_started_total{node=\"5\",kind=\"client\"} 0"));
+        assert!(lines.contains(&"sc_events_ingested_total 10"));
+        assert!(lines.contains(&"sc_event_ingestion_rate 2.5"));
+        assert!(lines.contains(&"sc_crashed_drones 1"));
+        assert_eq!(lines.last(), Some(&"# EOF"));
+    }
+}
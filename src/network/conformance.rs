@@ -0,0 +1,534 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+};
+
+use wg_2024::{
+    controller::DroneEvent,
+    network::{NodeId, SourceRoutingHeader},
+    packet::{FloodRequest, Nack, NackType, NodeType, Packet, PacketType},
+};
+
+use super::{node_kind::NodeKind, Network};
+
+/// violations kept for each drone to be shown in the detail view
+pub const KEPT_VIOLATIONS: usize = 10;
+
+/// rules of the WG protocol checked on every drone event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    /// the hop index was not advanced before forwarding, or points outside of the hops
+    HopIndex,
+    /// the packet was forwarded to a node that is not a neighbor
+    NextHop,
+    /// the type of a nack created by the drone does not match the situation
+    NackType,
+    /// the drone forwarded a packet of which it is the destination, or did not answer it with a
+    /// DestinationIsDrone nack
+    DestinationIsDrone,
+    /// a forwarded flood request does not end with the drone in its path trace
+    PathTrace,
+    /// a packet that is not an Ack, Nack or FloodResponse was sent through the controller
+    Shortcut,
+    /// a packet that is not a fragment was dropped
+    Drop,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 7] = [
+        Rule::HopIndex,
+        Rule::NextHop,
+        Rule::NackType,
+        Rule::DestinationIsDrone,
+        Rule::PathTrace,
+        Rule::Shortcut,
+        Rule::Drop,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::HopIndex => "hop index",
+            Rule::NextHop => "next hop",
+            Rule::NackType => "nack type",
+            Rule::DestinationIsDrone => "destination",
+            Rule::PathTrace => "path trace",
+            Rule::Shortcut => "shortcut",
+            Rule::Drop => "drop",
+        }
+    }
+}
+
+/// a broken rule, with the drone that broke it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub drone: NodeId,
+    pub rule: Rule,
+    pub detail: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule.name(), self.detail)
+    }
+}
+
+/// events checked for a drone and the rules it broke
+#[derive(Debug, Clone, Default)]
+pub struct Conformance {
+    pub checked: u64,
+    pub violations: BTreeMap<Rule, u64>,
+    /// most recent violations, newest first
+    pub recent: VecDeque<Violation>,
+}
+
+impl Conformance {
+    pub fn record(&mut self, violation: Violation) {
+        *self.violations.entry(violation.rule).or_default() += 1;
+        self.recent.push_front(violation);
+        self.recent.truncate(KEPT_VIOLATIONS);
+    }
+
+    pub fn total_violations(&self) -> u64 {
+        self.violations.values().sum()
+    }
+}
+
+/// conformance of all the drones of the same implementation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportCard {
+    pub implementation: String,
+    pub drones: Vec<NodeId>,
+    pub checked: u64,
+    pub violations: BTreeMap<Rule, u64>,
+}
+
+impl ReportCard {
+    pub fn passed(&self) -> bool {
+        self.violations.values().all(|n| *n == 0)
+    }
+}
+
+/// name of the implementation of a drone, taken from its thread name, e.g. "skylink#3" is a
+/// "skylink" drone
+pub fn implementation(thread_name: &str) -> &str {
+    match thread_name.split_once('#') {
+        Some((name, _)) if !name.is_empty() => name,
+        _ if thread_name.is_empty() => "unknown",
+        _ => thread_name,
+    }
+}
+
+/// groups the conformance of the drones by implementation, sorted by name
+pub fn report_cards(network: &Network) -> Vec<ReportCard> {
+    let mut cards: BTreeMap<&str, ReportCard> = BTreeMap::new();
+    for node in network.nodes() {
+        if let NodeKind::Drone { .. } = node.kind {
+            let name = implementation(&node.thread_name);
+            let card = cards.entry(name).or_insert_with(|| ReportCard {
+                implementation: name.to_string(),
+                drones: Vec::new(),
+                checked: 0,
+                violations: BTreeMap::new(),
+            });
+            card.drones.push(node.id);
+            card.checked += node.conformance.checked;
+            for (rule, n) in &node.conformance.violations {
+                *card.violations.entry(*rule).or_default() += n;
+            }
+        }
+    }
+    cards.into_values().collect()
+}
+
+/// checks an event of drone `id` against the protocol, the id is the one `save_droneevent`
+/// read from the packet
+///
+/// the event does not say which drone sent it, so when the previous hop of a forwarded packet
+/// is not a drone, or it already sent the very same packet just before, the packet can only come
+/// from the drone at the current hop, that did not advance the hop index
+pub fn check_drone_event(network: &Network, id: NodeId, event: &DroneEvent) -> Vec<Violation> {
+    let violation = |drone: NodeId, rule: Rule, detail: String| Violation {
+        drone,
+        rule,
+        detail,
+    };
+    let mut violations = Vec::new();
+    match event {
+        DroneEvent::PacketSent(packet) => match &packet.pack_type {
+            PacketType::FloodRequest(flood) => {
+                violations.extend(check_flood_request(network, &packet.routing_header, flood));
+            }
+            pack_type => {
+                violations.extend(check_forward(network, id, packet));
+                if let PacketType::Nack(nack) = pack_type {
+                    violations.extend(check_nack(network, id, nack, packet));
+                }
+            }
+        },
+        DroneEvent::PacketDropped(packet) => {
+            if !matches!(packet.pack_type, PacketType::MsgFragment(_)) {
+                violations.push(violation(
+                    id,
+                    Rule::Drop,
+                    format!("dropped {packet}, only fragments can be dropped"),
+                ));
+            }
+        }
+        DroneEvent::ControllerShortcut(packet) => match packet.pack_type {
+            PacketType::Ack(_) | PacketType::Nack(_) | PacketType::FloodResponse(_) => {}
+            _ => violations.push(violation(
+                id,
+                Rule::Shortcut,
+                format!("sent {packet} through the controller"),
+            )),
+        },
+    }
+    violations
+}
+
+fn is_drone(network: &Network, id: NodeId) -> bool {
+    matches!(
+        network.get_node_from_id(id).map(|n| n.kind),
+        Some(NodeKind::Drone { .. })
+    )
+}
+
+fn is_neighbor(network: &Network, id: NodeId, other: NodeId) -> bool {
+    network.graph().has_edge(id, other)
+}
+
+/// whether the last packet sent by the drone is the same as this one, a drone sends a packet
+/// twice only after a nack or an ack for it went back through it
+fn sent_just_before(network: &Network, id: NodeId, packet: &Packet) -> bool {
+    network
+        .get_node_from_id(id)
+        .and_then(|n| n.sent.front())
+        .is_some_and(|p| p == packet)
+}
+
+/// a forwarded packet must have the drone at the previous hop and a neighbor at the current one,
+/// and the drone at the last hop must not forward it
+fn check_forward(network: &Network, id: NodeId, packet: &Packet) -> Vec<Violation> {
+    let hops = &packet.routing_header.hops;
+    let i = packet.routing_header.hop_index;
+    match hops.get(i) {
+        None if i == hops.len() && is_drone(network, id) => vec![Violation {
+            drone: id,
+            rule: Rule::DestinationIsDrone,
+            detail: format!("forwarded a packet of which it is the destination, {hops:?}"),
+        }],
+        None => vec![Violation {
+            drone: id,
+            rule: Rule::HopIndex,
+            detail: format!("hop index {i} is past the end of {hops:?}"),
+        }],
+        Some(&next)
+            if is_drone(network, next)
+                && (!is_drone(network, id) || sent_just_before(network, id, packet)) =>
+        {
+            vec![Violation {
+                drone: next,
+                rule: Rule::HopIndex,
+                detail: format!("hop index {i} was not advanced, it still points at #{next}"),
+            }]
+        }
+        Some(&next) if !is_neighbor(network, id, next) => vec![Violation {
+            drone: id,
+            rule: Rule::NextHop,
+            detail: format!("forwarded to #{next}, that is not a neighbor"),
+        }],
+        Some(_) => Vec::new(),
+    }
+}
+
+/// the fragment the nack created by the drone answers, looked up among the packets sent by the
+/// node before it on the route
+fn nacked_fragment<'a>(
+    network: &'a Network,
+    id: NodeId,
+    nack: &Nack,
+    packet: &Packet,
+) -> Option<&'a Packet> {
+    let previous = *packet.routing_header.hops.get(1)?;
+    network.get_node_from_id(previous)?.sent.iter().find(|p| {
+        p.session_id == packet.session_id
+            && p.routing_header.current_hop() == Some(id)
+            && matches!(&p.pack_type, PacketType::MsgFragment(f) if f.fragment_index == nack.fragment_index)
+    })
+}
+
+/// a nack created by the drone, so starting from it, must have a type that fits
+fn check_nack(network: &Network, id: NodeId, nack: &Nack, packet: &Packet) -> Vec<Violation> {
+    let header = &packet.routing_header;
+    if header.hops.first() != Some(&id) {
+        return Vec::new();
+    }
+    let mut violations = Vec::new();
+    let detail = match nack.nack_type {
+        NackType::ErrorInRouting(n) if is_neighbor(network, id, n) => {
+            Some(format!("ErrorInRouting(#{n}) but #{n} is a neighbor"))
+        }
+        NackType::UnexpectedRecipient(n) if n != id => Some(format!(
+            "UnexpectedRecipient(#{n}) should carry the id of the drone"
+        )),
+        _ => None,
+    };
+    if let Some(detail) = detail {
+        violations.push(Violation {
+            drone: id,
+            rule: Rule::NackType,
+            detail,
+        });
+    }
+
+    // the fragment may not be in memory anymore, then the type cannot be checked
+    if let Some(fragment) = nacked_fragment(network, id, nack, packet) {
+        let destination = fragment.routing_header.hops.last() == Some(&id);
+        let detail = match nack.nack_type {
+            NackType::DestinationIsDrone if !destination => Some(format!(
+                "DestinationIsDrone but the destination of {} is not the drone",
+                fragment.routing_header
+            )),
+            nack_type if destination && nack_type != NackType::DestinationIsDrone => Some(format!(
+                "{nack_type:?} but the drone is the destination of {}",
+                fragment.routing_header
+            )),
+            _ => None,
+        };
+        if let Some(detail) = detail {
+            violations.push(Violation {
+                drone: id,
+                rule: Rule::DestinationIsDrone,
+                detail,
+            });
+        }
+    }
+    violations
+}
+
+/// a forwarded flood request must end with the drone that sent it, coming from a neighbor, and
+/// must not have gone through the drone already
+///
+/// the drone is the previous hop of the routing header, when the header does not name a drone
+/// it is taken from the end of the path trace, and then a drone that did not add itself cannot be
+/// told apart from the one before it
+fn check_flood_request(
+    network: &Network,
+    header: &SourceRoutingHeader,
+    flood: &FloodRequest,
+) -> Option<Violation> {
+    let trace = &flood.path_trace;
+    let drone = header
+        .previous_hop()
+        .or_else(|| trace.last().map(|(n, _)| *n))
+        .filter(|n| is_drone(network, *n))?;
+    let detail = match trace.as_slice() {
+        [.., last] if *last != (drone, NodeType::Drone) => {
+            format!(
+                "the path trace ends with #{} as {:?}, the drone did not add itself",
+                last.0, last.1
+            )
+        }
+        [] => "the path trace is empty, the drone did not add itself".to_string(),
+        [.., (prev, _), _] if !is_neighbor(network, drone, *prev) => {
+            format!("the path trace says it came from #{prev}, that is not a neighbor")
+        }
+        [rest @ .., _] if rest.iter().any(|(x, _)| *x == drone) => {
+            "forwarded a flood request that already went through the drone".to_string()
+        }
+        _ => return None,
+    };
+    Some(Violation {
+        drone,
+        rule: Rule::PathTrace,
+        detail,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use wg_2024::packet::{Ack, Fragment, Packet};
+
+    use super::*;
+    use crate::network::tests::line_network as network;
+
+    fn packet(pack_type: PacketType, hop_index: usize, hops: Vec<NodeId>) -> Packet {
+        Packet {
+            pack_type,
+            routing_header: SourceRoutingHeader { hop_index, hops },
+            session_id: 0,
+        }
+    }
+
+    fn rules(network: &Network, id: NodeId, event: &DroneEvent) -> Vec<(NodeId, Rule)> {
+        check_drone_event(network, id, event)
+            .iter()
+            .map(|v| (v.drone, v.rule))
+            .collect()
+    }
+
+    #[test]
+    fn violations() {
+        let network = network();
+        let fragment = PacketType::MsgFragment(Fragment {
+            fragment_index: 0,
+            total_n_fragments: 1,
+            length: 0,
+            data: [0; 128],
+        });
+        let ack = PacketType::Ack(Ack { fragment_index: 0 });
+
+        let ok = DroneEvent::PacketSent(packet(fragment.clone(), 2, vec![5, 4, 3]));
+        assert_eq!(rules(&network, 4, &ok), vec![]);
+        // drone 4 did not advance the hop index, so the previous hop is the client
+        let stuck = DroneEvent::PacketSent(packet(fragment.clone(), 1, vec![5, 4, 3]));
+        assert_eq!(rules(&network, 5, &stuck), vec![(4, Rule::HopIndex)]);
+        let far = DroneEvent::PacketSent(packet(fragment.clone(), 2, vec![5, 4, 1]));
+        assert_eq!(rules(&network, 4, &far), vec![(4, Rule::NextHop)]);
+        let past = DroneEvent::PacketSent(packet(fragment.clone(), 3, vec![5, 4, 3]));
+        assert_eq!(
+            rules(&network, 3, &past),
+            vec![(3, Rule::DestinationIsDrone)]
+        );
+
+        let nack = |nack_type| {
+            PacketType::Nack(Nack {
+                fragment_index: 0,
+                nack_type,
+            })
+        };
+        let routing =
+            DroneEvent::PacketSent(packet(nack(NackType::ErrorInRouting(3)), 1, vec![4, 5]));
+        assert_eq!(rules(&network, 4, &routing), vec![(4, Rule::NackType)]);
+        let unexpected = DroneEvent::PacketSent(packet(
+            nack(NackType::UnexpectedRecipient(4)),
+            1,
+            vec![4, 5],
+        ));
+        assert_eq!(rules(&network, 4, &unexpected), vec![]);
+
+        let flood = |path_trace, hop_index, hops| {
+            DroneEvent::PacketSent(packet(
+                PacketType::FloodRequest(FloodRequest {
+                    flood_id: 0,
+                    initiator_id: 5,
+                    path_trace,
+                }),
+                hop_index,
+                hops,
+            ))
+        };
+        let added = flood(vec![(5, NodeType::Client), (4, NodeType::Drone)], 0, vec![]);
+        assert_eq!(rules(&network, 4, &added), vec![]);
+        let looped = flood(
+            vec![
+                (4, NodeType::Drone),
+                (3, NodeType::Drone),
+                (4, NodeType::Drone),
+            ],
+            0,
+            vec![],
+        );
+        assert_eq!(rules(&network, 4, &looped), vec![(4, Rule::PathTrace)]);
+        // the sender is the previous hop of the header, not the last node of the trace
+        let forgot = flood(vec![(5, NodeType::Client)], 2, vec![5, 4, 3]);
+        assert_eq!(rules(&network, 5, &forgot), vec![(4, Rule::PathTrace)]);
+        let forgot = flood(
+            vec![(5, NodeType::Client), (4, NodeType::Drone)],
+            3,
+            vec![5, 4, 3, 2],
+        );
+        assert_eq!(rules(&network, 4, &forgot), vec![(3, Rule::PathTrace)]);
+
+        let dropped = DroneEvent::PacketDropped(packet(ack.clone(), 1, vec![4, 3]));
+        assert_eq!(rules(&network, 3, &dropped), vec![(3, Rule::Drop)]);
+        let shortcut = DroneEvent::ControllerShortcut(packet(fragment, 1, vec![4, 3]));
+        assert_eq!(rules(&network, 4, &shortcut), vec![(4, Rule::Shortcut)]);
+        let shortcut = DroneEvent::ControllerShortcut(packet(ack, 1, vec![4, 3]));
+        assert_eq!(rules(&network, 4, &shortcut), vec![]);
+    }
+
+    #[test]
+    fn drone_to_drone() {
+        let mut network = network();
+        let fragment = |fragment_index| {
+            PacketType::MsgFragment(Fragment {
+                fragment_index,
+                total_n_fragments: 2,
+                length: 0,
+                data: [0; 128],
+            })
+        };
+        let sent = packet(fragment(0), 2, vec![5, 4, 3, 2]);
+        network
+            .get_mut_node_from_id(4)
+            .unwrap()
+            .sent
+            .push_front(sent.clone());
+        // drone 3 sent the packet of drone 4 again without advancing the hop index
+        let stuck = DroneEvent::PacketSent(sent);
+        assert_eq!(rules(&network, 4, &stuck), vec![(3, Rule::HopIndex)]);
+        let next = DroneEvent::PacketSent(packet(fragment(1), 2, vec![5, 4, 3, 2]));
+        assert_eq!(rules(&network, 4, &next), vec![]);
+
+        let nack = |nack_type| {
+            DroneEvent::PacketSent(packet(
+                PacketType::Nack(Nack {
+                    fragment_index: 0,
+                    nack_type,
+                }),
+                1,
+                vec![3, 4, 5],
+            ))
+        };
+        let sent = packet(fragment(0), 2, vec![5, 4, 3]);
+        network
+            .get_mut_node_from_id(4)
+            .unwrap()
+            .sent
+            .push_front(sent);
+        assert_eq!(
+            rules(&network, 3, &nack(NackType::DestinationIsDrone)),
+            vec![]
+        );
+        assert_eq!(
+            rules(&network, 3, &nack(NackType::Dropped)),
+            vec![(3, Rule::DestinationIsDrone)]
+        );
+        let sent = packet(fragment(0), 2, vec![5, 4, 3, 2]);
+        network
+            .get_mut_node_from_id(4)
+            .unwrap()
+            .sent
+            .push_front(sent);
+        assert_eq!(
+            rules(&network, 3, &nack(NackType::DestinationIsDrone)),
+            vec![(3, Rule::DestinationIsDrone)]
+        );
+    }
+
+    #[test]
+    fn implementations() {
+        assert_eq!(implementation("skylink#3"), "skylink");
+        assert_eq!(implementation("rustafarian"), "rustafarian");
+        assert_eq!(implementation(""), "unknown");
+
+        let mut network = network();
+        for node in network.nodes_mut() {
+            node.thread_name = format!("{}#{}", if node.id < 3 { "a" } else { "b" }, node.id);
+        }
+        network.nodes_mut()[0].conformance.checked = 3;
+        network.nodes_mut()[0].conformance.record(Violation {
+            drone: 1,
+            rule: Rule::Drop,
+            detail: String::new(),
+        });
+        let cards = report_cards(&network);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].drones, vec![1, 2]);
+        assert_eq!(cards[0].checked, 3);
+        assert!(!cards[0].passed());
+        assert_eq!(cards[1].drones, vec![3, 4]);
+        assert!(cards[1].passed());
+    }
+}
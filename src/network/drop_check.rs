@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+/// fragments needed before the observed drops are compared with the pdr
+pub const MIN_CHECKED_FRAGMENTS: u64 = 30;
+/// z score over which a drone is suspicious, two sided 99.9% confidence
+pub const SUSPICIOUS_Z_SCORE: f64 = 3.29;
+
+/// why a drone is suspected of not following the protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum Suspicion {
+    /// the drone dropped packets that are not fragments, which must never be dropped
+    NonFragmentDrops(u64),
+    /// the drone drops a lot more or less fragments than its pdr says, it may be ignoring
+    /// `SetPacketDropRate`
+    DropRate {
+        observed: f64,
+        expected: f64,
+        z: f64,
+    },
+}
+
+impl Display for Suspicion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Suspicion::NonFragmentDrops(n) => {
+                write!(f, "dropped {n} packets that are not fragments")
+            }
+            Suspicion::DropRate {
+                observed,
+                expected,
+                z,
+            } => write!(
+                f,
+                "drop rate {observed:.3} while pdr says {expected:.3} (z={z:.1})"
+            ),
+        }
+    }
+}
+
+/// tests the fragments dropped by a drone against the pdr it had when each of them arrived
+///
+/// every fragment is a bernoulli trial with the pdr of that moment, so the number of drops has
+/// mean equal to the sum of the pdrs and variance equal to the sum of `pdr * (1 - pdr)`, which
+/// stays correct when the pdr changes; the binomial is approximated with a normal distribution
+#[derive(Debug, Clone, Default)]
+pub struct DropCheck {
+    fragments: u64,
+    dropped: u64,
+    expected: f64,
+    variance: f64,
+    non_fragment_drops: u64,
+}
+
+impl DropCheck {
+    /// records a fragment that reached the drone while it had the given pdr
+    pub fn record_fragment(&mut self, pdr: f32, dropped: bool) {
+        let p = f64::from(pdr);
+        self.fragments += 1;
+        self.dropped += u64::from(dropped);
+        self.expected += p;
+        self.variance += p * (1.0 - p);
+    }
+
+    pub fn record_non_fragment_drop(&mut self) {
+        self.non_fragment_drops += 1;
+    }
+
+    /// how many standard deviations the observed drops are from the expected ones, `None` while
+    /// there are too few fragments to tell
+    ///
+    /// with a pdr of 0 or 1 the outcome is certain, so any difference is infinitely unlikely
+    pub fn z_score(&self) -> Option<f64> {
+        let diff = self.dropped as f64 - self.expected;
+        if self.variance < f64::EPSILON {
+            return (self.fragments > 0).then(|| {
+                if diff.abs() < 0.5 {
+                    0.0
+                } else {
+                    diff.signum() * f64::INFINITY
+                }
+            });
+        }
+        (self.fragments >= MIN_CHECKED_FRAGMENTS).then(|| diff / self.variance.sqrt())
+    }
+
+    pub fn suspicions(&self) -> Vec<Suspicion> {
+        let mut suspicions = Vec::new();
+        if self.non_fragment_drops > 0 {
+            suspicions.push(Suspicion::NonFragmentDrops(self.non_fragment_drops));
+        }
+        if let Some(z) = self.z_score().filter(|z| z.abs() > SUSPICIOUS_Z_SCORE) {
+            let n = self.fragments as f64;
+            suspicions.push(Suspicion::DropRate {
+                observed: self.dropped as f64 / n,
+                expected: self.expected / n,
+                z,
+            });
+        }
+        suspicions
+    }
+
+    pub fn is_suspicious(&self) -> bool {
+        self.non_fragment_drops > 0 || self.z_score().is_some_and(|z| z.abs() > SUSPICIOUS_Z_SCORE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_rate() {
+        // a drone that drops one fragment out of ten with a pdr of 0.1 is fine
+        let mut honest = DropCheck::default();
+        for i in 0..200 {
+            honest.record_fragment(0.1, i % 10 == 0);
+        }
+        assert!(!honest.is_suspicious());
+
+        // one that ignored the change of pdr from 0.1 to 0.5 is not
+        let mut stubborn = DropCheck::default();
+        for i in 0..200 {
+            stubborn.record_fragment(if i < 100 { 0.1 } else { 0.5 }, i % 10 == 0);
+        }
+        assert!(matches!(
+            stubborn.suspicions().as_slice(),
+            [Suspicion::DropRate { z, .. }] if *z < -SUSPICIOUS_Z_SCORE
+        ));
+
+        // too few fragments to tell
+        let mut few = DropCheck::default();
+        few.record_fragment(0.5, true);
+        few.record_fragment(0.5, true);
+        assert_eq!(few.z_score(), None);
+
+        // a drone with pdr 0 must never drop
+        let mut certain = DropCheck::default();
+        certain.record_fragment(0.0, false);
+        assert_eq!(certain.z_score(), Some(0.0));
+        certain.record_fragment(0.0, true);
+        assert!(certain.is_suspicious());
+
+        let mut nack_dropper = DropCheck::default();
+        nack_dropper.record_non_fragment_drop();
+        assert_eq!(
+            nack_dropper.suspicions(),
+            vec![Suspicion::NonFragmentDrops(1)]
+        );
+    }
+}
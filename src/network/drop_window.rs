@@ -0,0 +1,134 @@
+use std::{collections::VecDeque, time::Instant};
+
+/// seconds over which the observed pdr is computed
+pub const DROP_WINDOW_SECS: u64 = 30;
+/// seconds of history kept to draw the observed pdr over time
+pub const DROP_HISTORY_SECS: u64 = 120;
+
+/// fragments forwarded and dropped by a drone during one second
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bucket {
+    sec: u64,
+    sent: u64,
+    dropped: u64,
+}
+
+/// keeps the fragments that reached a drone in the last seconds, to estimate the pdr it is
+/// actually applying, which unlike the lifetime ratio follows a change of pdr quickly
+#[derive(Debug, Clone)]
+pub struct DropWindow {
+    origin: Instant,
+    buckets: VecDeque<Bucket>,
+    /// seconds at which the pdr of the drone was changed
+    pdr_changes: VecDeque<u64>,
+}
+
+impl DropWindow {
+    pub fn new(origin: Instant) -> Self {
+        DropWindow {
+            origin,
+            buckets: VecDeque::new(),
+            pdr_changes: VecDeque::new(),
+        }
+    }
+
+    fn sec(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.origin).as_secs()
+    }
+
+    /// records a fragment that reached the drone, either forwarded or dropped
+    pub fn record(&mut self, at: Instant, dropped: bool) {
+        let sec = self.sec(at);
+        match self.buckets.back_mut() {
+            Some(b) if b.sec == sec => {
+                b.sent += 1;
+                b.dropped += u64::from(dropped);
+            }
+            _ => self.buckets.push_back(Bucket {
+                sec,
+                sent: 1,
+                dropped: u64::from(dropped),
+            }),
+        }
+        while self
+            .buckets
+            .front()
+            .is_some_and(|b| b.sec + DROP_HISTORY_SECS < sec)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    pub fn pdr_changed(&mut self, at: Instant) {
+        let sec = self.sec(at);
+        self.pdr_changes.push_back(sec);
+        while self
+            .pdr_changes
+            .front()
+            .is_some_and(|s| s + DROP_HISTORY_SECS < sec)
+        {
+            self.pdr_changes.pop_front();
+        }
+    }
+
+    /// ratio of dropped fragments in the `DROP_WINDOW_SECS` seconds that end at the second `sec`,
+    /// None if no fragment reached the drone
+    fn rate_at(&self, sec: u64) -> Option<f64> {
+        let (sent, dropped) = self
+            .buckets
+            .iter()
+            .filter(|b| b.sec <= sec && b.sec + DROP_WINDOW_SECS > sec)
+            .fold((0, 0), |(s, d), b| (s + b.sent, d + b.dropped));
+        (sent > 0).then(|| dropped as f64 / sent as f64)
+    }
+
+    /// observed pdr over the last `DROP_WINDOW_SECS` seconds
+    pub fn rate(&self, now: Instant) -> Option<f64> {
+        self.rate_at(self.sec(now))
+    }
+
+    /// observed pdr for each of the last `len` seconds, oldest first, with whether the pdr was
+    /// changed during that second
+    pub fn series(&self, now: Instant, len: u64) -> Vec<(Option<f64>, bool)> {
+        let now = self.sec(now);
+        (now.saturating_sub(len.saturating_sub(1))..=now)
+            .map(|sec| (self.rate_at(sec), self.pdr_changes.contains(&sec)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn window_follows_changes() {
+        let start = Instant::now();
+        let mut w = DropWindow::new(start);
+        assert_eq!(w.rate(start), None);
+
+        for i in 0..10 {
+            w.record(start + Duration::from_secs(i), i % 2 == 0);
+        }
+        assert_eq!(w.rate(start + Duration::from_secs(9)), Some(0.5));
+
+        // after the pdr is changed to 1 only drops are seen, the old fragments leave the window
+        w.pdr_changed(start + Duration::from_secs(10));
+        for i in 10..50 {
+            w.record(start + Duration::from_secs(i), true);
+        }
+        assert_eq!(w.rate(start + Duration::from_secs(49)), Some(1.0));
+
+        let series = w.series(start + Duration::from_secs(49), 45);
+        assert_eq!(series.len(), 45);
+        // the series starts at the 5th second
+        assert_eq!(series[4], (Some(0.5), false));
+        assert_eq!(series[5], (Some(6.0 / 11.0), true));
+        assert_eq!(series[44], (Some(1.0), false));
+
+        // nothing recorded for longer than the window
+        assert_eq!(w.rate(start + Duration::from_secs(100)), None);
+    }
+}
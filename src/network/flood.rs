@@ -0,0 +1,180 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use wg_2024::{
+    network::NodeId,
+    packet::{NodeType, PacketType},
+};
+
+/// floods that are remembered, older ones are forgotten
+pub const KEPT_FLOODS: usize = 50;
+
+/// propagation of one flood, rebuilt from the path traces of its requests and responses
+#[derive(Debug, Clone)]
+pub struct Flood {
+    pub initiator: NodeId,
+    pub flood_id: u64,
+    pub started: Instant,
+    pub last_seen: Instant,
+    /// for each node reached by the flood, the node it was first reached from
+    pub parent: BTreeMap<NodeId, NodeId>,
+    pub node_types: BTreeMap<NodeId, NodeType>,
+    /// links along which the request reached a node that had already been reached from another one
+    pub redundant: BTreeSet<(NodeId, NodeId)>,
+    /// paths from the initiator to each node that answered, without duplicates
+    pub responses: Vec<Vec<NodeId>>,
+    /// flood request packets sent
+    pub requests: u64,
+}
+
+impl Flood {
+    fn new(initiator: NodeId, flood_id: u64, at: Instant) -> Self {
+        Flood {
+            initiator,
+            flood_id,
+            started: at,
+            last_seen: at,
+            parent: BTreeMap::new(),
+            node_types: BTreeMap::new(),
+            redundant: BTreeSet::new(),
+            responses: Vec::new(),
+            requests: 0,
+        }
+    }
+
+    /// adds the links of the path trace to the tree, the trace is expected to start from the
+    /// initiator, when it does not the initiator is put in front of it
+    fn record_trace(&mut self, trace: &[(NodeId, NodeType)]) {
+        let mut previous = self.initiator;
+        for (id, node_type) in trace {
+            self.node_types.insert(*id, *node_type);
+            if *id == self.initiator {
+                previous = *id;
+                continue;
+            }
+            match self.parent.get(id) {
+                None => {
+                    self.parent.insert(*id, previous);
+                }
+                Some(p) if *p != previous => {
+                    self.redundant.insert((previous, *id));
+                }
+                Some(_) => {}
+            }
+            previous = *id;
+        }
+    }
+
+    /// time between the first and the last packet of the flood
+    pub fn duration(&self) -> Duration {
+        self.last_seen.saturating_duration_since(self.started)
+    }
+
+    /// drones the request got to
+    pub fn drones_reached(&self) -> usize {
+        self.node_types
+            .iter()
+            .filter(|(id, t)| **id != self.initiator && **t == NodeType::Drone)
+            .count()
+    }
+
+    /// edges of the propagation tree, from parent to child
+    pub fn tree_edges(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.parent.iter().map(|(child, parent)| (*parent, *child))
+    }
+}
+
+/// floods seen in the network, newest first
+#[derive(Debug, Default)]
+pub struct Floods {
+    pub floods: VecDeque<Flood>,
+}
+
+impl Floods {
+    fn get_or_insert(&mut self, initiator: NodeId, flood_id: u64, at: Instant) -> &mut Flood {
+        let idx = match self
+            .floods
+            .iter()
+            .position(|f| f.initiator == initiator && f.flood_id == flood_id)
+        {
+            Some(idx) => idx,
+            None => {
+                self.floods.push_front(Flood::new(initiator, flood_id, at));
+                self.floods.truncate(KEPT_FLOODS);
+                0
+            }
+        };
+        &mut self.floods[idx]
+    }
+
+    /// adds a flood request or response that was sent to the flood it belongs to, responses are
+    /// matched using the first node of their path trace as initiator
+    pub fn record(&mut self, pack_type: &PacketType, at: Instant) {
+        match pack_type {
+            PacketType::FloodRequest(request) => {
+                let flood = self.get_or_insert(request.initiator_id, request.flood_id, at);
+                flood.requests += 1;
+                flood.last_seen = at;
+                flood.record_trace(&request.path_trace);
+            }
+            PacketType::FloodResponse(response) => {
+                let Some((initiator, _)) = response.path_trace.first() else {
+                    return;
+                };
+                let flood = self.get_or_insert(*initiator, response.flood_id, at);
+                flood.last_seen = at;
+                flood.record_trace(&response.path_trace);
+                let path: Vec<NodeId> = response.path_trace.iter().map(|(id, _)| *id).collect();
+                if !flood.responses.contains(&path) {
+                    flood.responses.push(path);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wg_2024::packet::{FloodRequest, FloodResponse};
+
+    use super::*;
+
+    #[test]
+    fn propagation_tree() {
+        use NodeType::{Client, Drone, Server};
+        let mut floods = Floods::default();
+        let now = Instant::now();
+        let request = |path_trace: Vec<(NodeId, NodeType)>| {
+            PacketType::FloodRequest(FloodRequest {
+                flood_id: 7,
+                initiator_id: 5,
+                path_trace,
+            })
+        };
+        floods.record(&request(vec![(5, Client)]), now);
+        floods.record(&request(vec![(5, Client), (4, Drone)]), now);
+        floods.record(&request(vec![(5, Client), (4, Drone), (3, Drone)]), now);
+        // 3 answers the request that came back from 6
+        let response = PacketType::FloodResponse(FloodResponse {
+            flood_id: 7,
+            path_trace: vec![(5, Client), (4, Drone), (6, Server), (3, Drone)],
+        });
+        floods.record(&response, now + Duration::from_millis(30));
+        floods.record(&response, now + Duration::from_millis(40));
+
+        assert_eq!(floods.floods.len(), 1);
+        let flood = &floods.floods[0];
+        assert_eq!(flood.requests, 3);
+        assert_eq!(
+            flood.tree_edges().collect::<Vec<_>>(),
+            vec![(4, 3), (5, 4), (4, 6)]
+        );
+        assert_eq!(flood.redundant, BTreeSet::from([(6, 3)]));
+        assert_eq!(flood.responses, vec![vec![5, 4, 6, 3]]);
+        assert_eq!(flood.drones_reached(), 2);
+        assert_eq!(flood.duration(), Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+use wg_2024::{network::NodeId, packet::PacketType};
+
+use super::node_representation::NodeRepresentation;
+use super::node_store::NodeStore;
+
+/// an undirected edge, with the smaller id first
+pub type EdgeKey = (NodeId, NodeId);
+
+pub fn edge_key(a: NodeId, b: NodeId) -> EdgeKey {
+    (a.min(b), a.max(b))
+}
+
+/// what is known about an edge while it is part of the network
+#[derive(Debug, Clone, Default)]
+pub struct Edge {
+    /// last packet that traveled on the edge, and when
+    pub last_packet: Option<(PacketType, Instant)>,
+}
+
+/// the edges a node had when it crashed or failed, it has none until it is revived
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Detached {
+    pub neighbors: BTreeSet<NodeId>,
+    /// whether the node failed, its former edges are then drawn dimmed
+    pub failed: bool,
+}
+
+/// the nodes of the network and the undirected edges between them, the only place where the
+/// topology is stored
+///
+/// every node has a set of neighbors, and an edge is in `edges` exactly when each of its ends is
+/// in the set of the other, a detached node has no edges, in debug builds this is checked after
+/// every change
+#[derive(Debug, Default)]
+pub struct Graph {
+    nodes: NodeStore,
+    edges: HashMap<EdgeKey, Edge>,
+    neighbors: HashMap<NodeId, BTreeSet<NodeId>>,
+    detached: HashMap<NodeId, Detached>,
+}
+
+impl Graph {
+    pub fn nodes(&self) -> &NodeStore {
+        &self.nodes
+    }
+
+    /// the nodes can be changed but not added or removed, and their ids must stay the same
+    pub fn nodes_mut(&mut self) -> &mut NodeStore {
+        &mut self.nodes
+    }
+
+    /// neighbors of the node in increasing order, none if it is not in the graph
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.neighbors.get(&id).into_iter().flatten().copied()
+    }
+
+    pub fn degree(&self, id: NodeId) -> usize {
+        self.neighbors.get(&id).map_or(0, BTreeSet::len)
+    }
+
+    pub fn has_edge(&self, a: NodeId, b: NodeId) -> bool {
+        self.edges.contains_key(&edge_key(a, b))
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (EdgeKey, &Edge)> {
+        self.edges.iter().map(|(k, e)| (*k, e))
+    }
+
+    /// neighbors the node had when it crashed or failed, in increasing order
+    pub fn previous_neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.detached
+            .get(&id)
+            .into_iter()
+            .flat_map(|d| d.neighbors.iter().copied())
+    }
+
+    /// the former edges of the nodes that failed
+    pub fn failed_edges(&self) -> impl Iterator<Item = EdgeKey> + '_ {
+        self.detached
+            .iter()
+            .filter(|(_, d)| d.failed)
+            .flat_map(|(id, d)| d.neighbors.iter().map(|n| edge_key(*id, *n)))
+    }
+
+    pub(super) fn edge_mut(&mut self, a: NodeId, b: NodeId) -> Option<&mut Edge> {
+        self.edges.get_mut(&edge_key(a, b))
+    }
+
+    /// adds a node without edges at the end of the list
+    pub(super) fn add_node(&mut self, node: NodeRepresentation) -> Result<(), &'static str> {
+        let id = node.id;
+        self.nodes.push(node)?;
+        self.neighbors.insert(id, BTreeSet::new());
+        self.debug_check();
+        Ok(())
+    }
+
+    /// removes the node together with its edges, current and former
+    pub(super) fn remove_node(&mut self, id: NodeId) -> Option<NodeRepresentation> {
+        self.remove_edges_of(id);
+        self.neighbors.remove(&id);
+        self.detached.remove(&id);
+        for d in self.detached.values_mut() {
+            d.neighbors.remove(&id);
+        }
+        let node = self.nodes.remove(id);
+        self.debug_check();
+        node
+    }
+
+    /// adds an edge between two nodes of the graph with the given metadata, returns whether it
+    /// existed already, in which case it is left as it was
+    pub(super) fn insert_edge(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        edge: Edge,
+    ) -> Result<bool, &'static str> {
+        if a == b {
+            return Err("cannot connect node to itself");
+        }
+        if !self.nodes.contains(a) || !self.nodes.contains(b) {
+            return Err("cannot connect to a node that is not in the network");
+        }
+        if self.detached.contains_key(&a) || self.detached.contains_key(&b) {
+            return Err("cannot connect a node that crashed or failed");
+        }
+        if self.has_edge(a, b) {
+            return Ok(true);
+        }
+        self.edges.insert(edge_key(a, b), edge);
+        self.neighbors.entry(a).or_default().insert(b);
+        self.neighbors.entry(b).or_default().insert(a);
+        self.debug_check();
+        Ok(false)
+    }
+
+    /// adds an edge that has not been used yet, see `insert_edge`
+    pub(super) fn add_edge(&mut self, a: NodeId, b: NodeId) -> Result<bool, &'static str> {
+        self.insert_edge(a, b, Edge::default())
+    }
+
+    pub(super) fn remove_edge(&mut self, a: NodeId, b: NodeId) -> Option<Edge> {
+        let edge = self.edges.remove(&edge_key(a, b))?;
+        if let Some(n) = self.neighbors.get_mut(&a) {
+            n.remove(&b);
+        }
+        if let Some(n) = self.neighbors.get_mut(&b) {
+            n.remove(&a);
+        }
+        self.debug_check();
+        Some(edge)
+    }
+
+    /// removes every edge of the node, returns the neighbors it had with the removed edges
+    pub(super) fn remove_edges_of(&mut self, id: NodeId) -> Vec<(NodeId, Edge)> {
+        let neighbors: Vec<NodeId> = self.neighbors(id).collect();
+        neighbors
+            .into_iter()
+            .filter_map(|n| self.remove_edge(id, n).map(|e| (n, e)))
+            .collect()
+    }
+
+    /// marks a node without edges as crashed or failed, remembering the neighbors it had
+    pub(super) fn detach(&mut self, id: NodeId, detached: Detached) {
+        self.detached.insert(id, detached);
+        self.debug_check();
+    }
+
+    /// forgets that the node crashed or failed, so that it can be connected again
+    pub(super) fn reattach(&mut self, id: NodeId) -> Option<Detached> {
+        self.detached.remove(&id)
+    }
+
+    fn debug_check(&self) {
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.check_invariants() {
+            panic!("graph invariant broken: {e}");
+        }
+    }
+
+    /// checks that the edges and the neighbors of the nodes describe the same topology, and that
+    /// the nodes that crashed or failed are not connected
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.neighbors.len() != self.nodes.len() {
+            return Err(format!(
+                "{} nodes but {} sets of neighbors",
+                self.nodes.len(),
+                self.neighbors.len()
+            ));
+        }
+        for (pos, node) in self.nodes.iter().enumerate() {
+            if self.nodes.position(node.id) != Some(pos) {
+                return Err(format!("#{} is not indexed at position {pos}", node.id));
+            }
+            if !self.neighbors.contains_key(&node.id) {
+                return Err(format!("#{} has no set of neighbors", node.id));
+            }
+        }
+        for &(a, b) in self.edges.keys() {
+            if a >= b {
+                return Err(format!("edge ({a}, {b}) is not in order"));
+            }
+            if !self.nodes.contains(a) || !self.nodes.contains(b) {
+                return Err(format!("edge ({a}, {b}) has an end that is not a node"));
+            }
+        }
+        let mut ends = 0;
+        for (id, neighbors) in &self.neighbors {
+            for n in neighbors {
+                if !self.edges.contains_key(&edge_key(*id, *n)) {
+                    return Err(format!("#{id} has neighbor #{n} without an edge"));
+                }
+            }
+            ends += neighbors.len();
+        }
+        // every edge was found from both of its ends
+        if ends != 2 * self.edges.len() {
+            return Err(format!(
+                "{} edges but {ends} neighbors in total",
+                self.edges.len()
+            ));
+        }
+        for (id, detached) in &self.detached {
+            if !self.nodes.contains(*id) {
+                return Err(format!("#{id} is detached but it is not a node"));
+            }
+            if self.degree(*id) > 0 {
+                return Err(format!("#{id} is detached but it still has edges"));
+            }
+            if let Some(n) = detached
+                .neighbors
+                .iter()
+                .find(|n| **n == *id || !self.nodes.contains(**n))
+            {
+                return Err(format!("#{id} had #{n} as neighbor, that is not a node"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::node_kind::NodeKind;
+
+    fn graph(ids: &[NodeId]) -> Graph {
+        let mut graph = Graph::default();
+        for id in ids {
+            graph
+                .add_node(NodeRepresentation::new(*id, 0, 0, NodeKind::Client))
+                .unwrap();
+        }
+        graph
+    }
+
+    #[test]
+    fn edges_and_neighbors_agree() {
+        let mut g = graph(&[1, 2, 3, 4]);
+        assert_eq!(g.add_edge(3, 1), Ok(false));
+        assert_eq!(g.add_edge(1, 3), Ok(true));
+        assert_eq!(g.add_edge(1, 2), Ok(false));
+        assert_eq!(g.add_edge(4, 1), Ok(false));
+        assert!(g.add_edge(1, 1).is_err());
+        assert!(g.add_edge(1, 9).is_err());
+        assert_eq!(g.neighbors(1).collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(g.neighbors(3).collect::<Vec<_>>(), vec![1]);
+        assert!(g.has_edge(3, 1) && g.has_edge(1, 3));
+        assert_eq!(g.edges().count(), 3);
+
+        g.edge_mut(2, 1).unwrap().last_packet = Some((
+            PacketType::Ack(wg_2024::packet::Ack { fragment_index: 0 }),
+            Instant::now(),
+        ));
+        let removed = g.remove_edges_of(1);
+        assert_eq!(
+            removed.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert!(removed[0].1.last_packet.is_some());
+        assert_eq!(g.degree(1), 0);
+        assert_eq!(g.edges().count(), 0);
+
+        g.add_edge(2, 3).unwrap();
+        g.add_edge(3, 4).unwrap();
+        let node = g.remove_node(3).unwrap();
+        assert_eq!(node.id, 3);
+        assert_eq!(g.edges().count(), 0);
+        assert_eq!(g.degree(2), 0);
+        assert_eq!(g.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn detached_nodes() {
+        let mut g = graph(&[1, 2, 3]);
+        g.add_edge(1, 2).unwrap();
+        g.add_edge(1, 3).unwrap();
+        let neighbors = g.remove_edges_of(1).into_iter().map(|(n, _)| n).collect();
+        g.detach(
+            1,
+            Detached {
+                neighbors,
+                failed: true,
+            },
+        );
+        assert!(g.add_edge(1, 2).is_err());
+        assert_eq!(g.previous_neighbors(1).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(g.failed_edges().count(), 2);
+
+        g.remove_node(3);
+        assert_eq!(g.previous_neighbors(1).collect::<Vec<_>>(), vec![2]);
+        assert!(g.reattach(1).is_some());
+        assert_eq!(g.add_edge(1, 2), Ok(false));
+        assert_eq!(g.failed_edges().count(), 0);
+        assert_eq!(g.check_invariants(), Ok(()));
+    }
+}
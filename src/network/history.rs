@@ -0,0 +1,339 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use messages::Message;
+use serde::{de::DeserializeOwned, Serialize};
+use wg_2024::{network::NodeId, packet::Packet};
+
+use super::node_kind::NodeKind;
+use super::Network;
+
+/// rows of each page of the history read back from disk
+pub const HISTORY_PAGE: usize = 100;
+
+/// kinds of events that are kept in memory up to their own limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventCategory {
+    Sent,
+    Dropped,
+    Shortcutted,
+    /// messages sent and received
+    Messages,
+}
+
+/// how many events of each category are kept in memory for each node, older ones are written to
+/// the history directory if there is one, otherwise they are discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub sent: usize,
+    pub dropped: usize,
+    pub shortcutted: usize,
+    pub messages: usize,
+}
+
+impl Retention {
+    /// the same limit for every category
+    pub fn uniform(limit: usize) -> Self {
+        Retention {
+            sent: limit,
+            dropped: limit,
+            shortcutted: limit,
+            messages: limit,
+        }
+    }
+
+    pub fn get(&self, category: EventCategory) -> usize {
+        match category {
+            EventCategory::Sent => self.sent,
+            EventCategory::Dropped => self.dropped,
+            EventCategory::Shortcutted => self.shortcutted,
+            EventCategory::Messages => self.messages,
+        }
+    }
+}
+
+/// tables of the detail window, each one has its own history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HistoryTable {
+    Sent,
+    Dropped,
+    Shortcutted,
+    MessagesSent,
+    MessagesReceived,
+}
+
+impl HistoryTable {
+    /// the table shown in the tab `tab` of the detail window of a node of the given kind
+    pub fn of(kind: NodeKind, tab: usize) -> Option<Self> {
+        match (tab, kind) {
+            (0, _) => Some(HistoryTable::Sent),
+            (1, NodeKind::Drone { .. }) => Some(HistoryTable::Dropped),
+            (2, NodeKind::Drone { .. }) => Some(HistoryTable::Shortcutted),
+            (1, _) => Some(HistoryTable::MessagesSent),
+            (2, _) => Some(HistoryTable::MessagesReceived),
+            _ => None,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            HistoryTable::Sent => "sent",
+            HistoryTable::Dropped => "dropped",
+            HistoryTable::Shortcutted => "shortcutted",
+            HistoryTable::MessagesSent => "messages_sent",
+            HistoryTable::MessagesReceived => "messages_received",
+        }
+    }
+}
+
+/// append only file with one json event per line, oldest first, the offset of each line is kept
+/// so that any range can be read back
+#[derive(Debug)]
+struct SpillLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    offsets: Vec<u64>,
+    end: u64,
+}
+
+impl SpillLog {
+    fn create(path: PathBuf) -> Result<Self, String> {
+        let file =
+            File::create(&path).map_err(|e| format!("cannot create {}: {e}", path.display()))?;
+        Ok(SpillLog {
+            path,
+            writer: BufWriter::new(file),
+            offsets: Vec::new(),
+            end: 0,
+        })
+    }
+
+    fn append<T: Serialize>(&mut self, event: &T) -> Result<(), String> {
+        let mut line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.writer
+            .write_all(line.as_bytes())
+            .map_err(|e| format!("cannot write {}: {e}", self.path.display()))?;
+        self.offsets.push(self.end);
+        self.end += line.len() as u64;
+        Ok(())
+    }
+
+    /// events from `start` to `end` excluded, oldest first
+    fn read<T: DeserializeOwned>(&mut self, start: usize, end: usize) -> Result<Vec<T>, String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("cannot write {}: {e}", self.path.display()))?;
+        let Some(from) = self.offsets.get(start) else {
+            return Ok(Vec::new());
+        };
+        let to = self.offsets.get(end).copied().unwrap_or(self.end);
+        let mut file = File::open(&self.path)
+            .map_err(|e| format!("cannot read {}: {e}", self.path.display()))?;
+        file.seek(SeekFrom::Start(*from))
+            .map_err(|e| format!("cannot read {}: {e}", self.path.display()))?;
+        BufReader::new(file.take(to - from))
+            .lines()
+            .map(|line| {
+                let line = line.map_err(|e| format!("cannot read {}: {e}", self.path.display()))?;
+                serde_json::from_str(&line).map_err(|e| format!("malformed history event: {e}"))
+            })
+            .collect()
+    }
+}
+
+/// rows of a page of the history of a table, newest first
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryRows {
+    Packets(Vec<Packet>),
+    /// messages with whether they were sent successfully, received ones always are
+    Messages(Vec<(Message, bool)>),
+}
+
+/// page of older events shown in the detail window instead of the ones kept in memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryPage {
+    pub node: NodeId,
+    pub table: HistoryTable,
+    /// from 1, the page with the most recent events that are on disk
+    pub page: usize,
+    pub pages: usize,
+    pub rows: HistoryRows,
+}
+
+impl HistoryPage {
+    /// the packet of the row, or for messages the newest packet of their session that is still
+    /// in memory
+    pub fn selected_packet<'a>(
+        &'a self,
+        network: &'a Network,
+        selected: usize,
+    ) -> Option<&'a Packet> {
+        match &self.rows {
+            HistoryRows::Packets(packets) => packets.get(selected),
+            HistoryRows::Messages(messages) => {
+                let (message, _) = messages.get(selected)?;
+                network.last_packet_of_session(message.source, message.session_id)
+            }
+        }
+    }
+}
+
+/// events that are not in memory anymore, written to one log per node and table
+#[derive(Debug)]
+pub struct History {
+    pub retention: Retention,
+    dir: Option<PathBuf>,
+    logs: HashMap<(NodeId, HistoryTable), SpillLog>,
+}
+
+impl History {
+    pub fn new(retention: Retention) -> Self {
+        History {
+            retention,
+            dir: None,
+            logs: HashMap::new(),
+        }
+    }
+
+    /// starts writing the events that do not fit in memory anymore to `dir`, the history written
+    /// so far is forgotten
+    pub fn set_dir(&mut self, dir: impl Into<PathBuf>) -> Result<(), String> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| format!("cannot create {}: {e}", dir.display()))?;
+        self.logs.clear();
+        self.dir = Some(dir);
+        Ok(())
+    }
+
+    /// writes an event that was removed from memory to the history, without a directory the
+    /// event is discarded
+    pub fn spill<T: Serialize>(
+        &mut self,
+        node: NodeId,
+        table: HistoryTable,
+        event: &T,
+    ) -> Result<(), String> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let log = match self.logs.entry((node, table)) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => e.insert(SpillLog::create(
+                dir.join(format!("{node}_{}.jsonl", table.file_name())),
+            )?),
+        };
+        log.append(event)
+    }
+
+    /// events of the table that are on disk
+    pub fn spilled(&self, node: NodeId, table: HistoryTable) -> usize {
+        self.logs.get(&(node, table)).map_or(0, |l| l.offsets.len())
+    }
+
+    pub fn pages(&self, node: NodeId, table: HistoryTable) -> usize {
+        self.spilled(node, table).div_ceil(HISTORY_PAGE)
+    }
+
+    /// reads back the page `page` of the table, counting from 1 for the most recent events on
+    /// disk, the rows are newest first
+    pub fn load(
+        &mut self,
+        node: NodeId,
+        table: HistoryTable,
+        page: usize,
+    ) -> Result<HistoryPage, String> {
+        let pages = self.pages(node, table);
+        if page == 0 || page > pages {
+            return Err(format!("there is no page {page} of {pages}"));
+        }
+        let spilled = self.spilled(node, table);
+        let end = spilled - (page - 1) * HISTORY_PAGE;
+        let start = end.saturating_sub(HISTORY_PAGE);
+        let Some(log) = self.logs.get_mut(&(node, table)) else {
+            unreachable!("the table has {pages} pages on disk but no log");
+        };
+        let rows = match table {
+            HistoryTable::Sent | HistoryTable::Dropped | HistoryTable::Shortcutted => {
+                let mut packets: Vec<Packet> = log.read(start, end)?;
+                packets.reverse();
+                HistoryRows::Packets(packets)
+            }
+            HistoryTable::MessagesSent => {
+                let mut messages: Vec<(Message, bool)> = log.read(start, end)?;
+                messages.reverse();
+                HistoryRows::Messages(messages)
+            }
+            HistoryTable::MessagesReceived => {
+                let messages: Vec<Message> = log.read(start, end)?;
+                HistoryRows::Messages(messages.into_iter().rev().map(|m| (m, true)).collect())
+            }
+        };
+        Ok(HistoryPage {
+            node,
+            table,
+            page,
+            pages,
+            rows,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wg_2024::{
+        network::SourceRoutingHeader,
+        packet::{Ack, PacketType},
+    };
+
+    use super::*;
+
+    fn ack(session_id: u64) -> Packet {
+        Packet {
+            pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 1,
+                hops: vec![1, 2],
+            },
+            session_id,
+        }
+    }
+
+    #[test]
+    fn paging() {
+        let dir = std::env::temp_dir().join(format!("sc-history-test-{}", std::process::id()));
+        let mut history = History::new(Retention::uniform(10));
+        // without a directory events are discarded
+        history.spill(1, HistoryTable::Sent, &ack(0)).unwrap();
+        assert_eq!(history.spilled(1, HistoryTable::Sent), 0);
+
+        history.set_dir(&dir).unwrap();
+        for session_id in 0..250 {
+            history
+                .spill(1, HistoryTable::Sent, &ack(session_id))
+                .unwrap();
+        }
+        assert_eq!(history.pages(1, HistoryTable::Sent), 3);
+        assert_eq!(history.pages(1, HistoryTable::Dropped), 0);
+
+        let page = history.load(1, HistoryTable::Sent, 1).unwrap();
+        let HistoryRows::Packets(packets) = &page.rows else {
+            panic!("sent table should have packets");
+        };
+        assert_eq!(packets.len(), HISTORY_PAGE);
+        assert_eq!(packets[0], ack(249));
+        assert_eq!(packets[99], ack(150));
+
+        let page = history.load(1, HistoryTable::Sent, 3).unwrap();
+        assert_eq!(
+            page.rows,
+            HistoryRows::Packets((0..50).rev().map(ack).collect())
+        );
+        assert!(history.load(1, HistoryTable::Sent, 4).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,55 +1,102 @@
 use std::{
-    borrow::BorrowMut,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     time::Instant,
 };
 
+use flood::Floods;
+use graph::{edge_key, Detached, EdgeKey, Graph};
+use nacks::Nacks;
 use node_kind::NodeKind;
 use node_representation::NodeRepresentation;
-use wg_2024::{config::Config, network::NodeId, packet::PacketType};
+use node_stats::PacketCounts;
+use node_store::NodeStore;
+use reassembly::{PayloadDecoder, Sessions};
+use throughput::Throughput;
+use wg_2024::{
+    config::{Client, Config, Drone, Server},
+    network::NodeId,
+    packet::{Fragment, Packet, PacketType},
+};
 
+pub mod conformance;
+pub mod drop_check;
+pub mod drop_window;
+pub mod flood;
+pub mod graph;
+pub mod history;
+pub mod nacks;
 pub mod node_kind;
 pub mod node_representation;
+pub mod node_stats;
+pub mod node_store;
+pub mod reassembly;
+pub mod throughput;
+pub mod topology_diff;
 
 #[derive(Debug, Default)]
 pub struct Network {
-    pub nodes: Vec<NodeRepresentation>,
-    pub edges: HashMap<(NodeId, NodeId), Option<(PacketType, Instant)>>,
+    /// nodes and the edges between them
+    graph: Graph,
+    /// packets that went through each edge, kept after the edge is removed
+    pub edge_stats: HashMap<EdgeKey, PacketCounts>,
+    /// per second traffic of the whole network and of each node, drawn in the metrics window
+    pub throughput: Throughput,
+    /// floods rebuilt from the flood requests and responses that were sent
+    pub floods: Floods,
+    /// nacks grouped by the node that created them and by session
+    pub nacks: Nacks,
+    /// fragments sent by clients and servers, reassembled per session
+    pub sessions: Sessions,
 }
 
 impl Network {
     /// creates a network from the given config, populating nodes and edges accordingly, and then
     /// checking if the resulting network is valid
     pub fn new(cfg: &Config) -> Result<Self, &'static str> {
-        let nodes: Vec<NodeRepresentation> = Vec::new();
-        let edges = HashMap::new();
-
-        let mut model = Self { nodes, edges };
+        let mut model = Self {
+            graph: Graph::default(),
+            edge_stats: HashMap::new(),
+            throughput: Throughput::default(),
+            floods: Floods::default(),
+            nacks: Nacks::default(),
+            sessions: Sessions::default(),
+        };
 
         for d in &cfg.drone {
-            model.nodes.push(NodeRepresentation::new_from_cfgdrone(d));
+            model
+                .graph
+                .add_node(NodeRepresentation::new_from_cfgdrone(d))?;
         }
         for s in &cfg.server {
-            model.nodes.push(NodeRepresentation::new_from_cfgserver(s));
+            model
+                .graph
+                .add_node(NodeRepresentation::new_from_cfgserver(s))?;
         }
         for c in &cfg.client {
-            model.nodes.push(NodeRepresentation::new_from_cfgclient(c));
+            model
+                .graph
+                .add_node(NodeRepresentation::new_from_cfgclient(c))?;
         }
 
-        for d in &cfg.drone {
-            for to in &d.connected_node_ids {
-                let _ = model.add_edge_unchecked(d.id, *to);
-            }
-        }
-        for s in &cfg.server {
-            for to in &s.connected_drone_ids {
-                let _ = model.add_edge_unchecked(s.id, *to);
-            }
-        }
-        for c in &cfg.client {
-            for to in &c.connected_drone_ids {
-                let _ = model.add_edge_unchecked(c.id, *to);
+        let drone_edges = cfg
+            .drone
+            .iter()
+            .flat_map(|d| d.connected_node_ids.iter().map(|to| (d.id, *to)));
+        let server_edges = cfg
+            .server
+            .iter()
+            .flat_map(|s| s.connected_drone_ids.iter().map(|to| (s.id, *to)));
+        let client_edges = cfg
+            .client
+            .iter()
+            .flat_map(|c| c.connected_drone_ids.iter().map(|to| (c.id, *to)));
+        for (from, to) in drone_edges.chain(server_edges).chain(client_edges) {
+            // Rule 1: A node cannot have itself in its adjacency list
+            if from == to {
+                return Err("A node cannot have itself in its adjacency list");
             }
+            // both ends usually list the edge, it is added only once
+            model.graph.add_edge(from, to)?;
         }
         match model.is_valid() {
             Ok(()) => Ok(model),
@@ -59,139 +106,84 @@ impl Network {
 
     /// check that the current state of the network respects WG rules, if not, returns a
     /// descriptive error
-    fn is_valid(&mut self) -> Result<(), &'static str> {
+    ///
+    /// rule 1 (no node is its own neighbor) is enforced by `Graph` and rule 5 (no duplicate node
+    /// IDs) by `NodeStore`, when edges and nodes are added
+    fn is_valid(&self) -> Result<(), &'static str> {
         use NodeKind::{Client, Drone, Server};
 
-        let mut node_ids = HashSet::new();
-        let mut drones = HashSet::new();
-        let mut graph = HashMap::new();
-
-        let mut crashed_drones = HashSet::new();
-        for node in &self.nodes {
-            if let Drone { crashed: true, .. } = node.kind {
-                crashed_drones.insert(node.id);
-            }
-        }
-
-        // Build the graph and collect valid drones
-        for node in &self.nodes {
-            // Skip crashed drones
-            if let Drone { crashed: true, .. } = node.kind {
-                continue; // Ignore crashed drones
-            }
-
-            // Rule 1: A node cannot have itself in its adjacency list
-            if node.adj.contains(&node.id) {
-                return Err("A node cannot have itself in its adjacency list");
-            }
-
-            // Rule 5: No duplicate node IDs
-            if !node_ids.insert(node.id) {
-                return Err("Duplicate node_id found in Network Initialization File");
-            }
-
-            // Collect drones for later verification, ignoring crashed drones
-            if matches!(node.kind, Drone { .. }) {
-                drones.insert(node.id);
-            }
-
-            // Build adjacency graph, excluding crashed drones
-            graph.insert(
-                node.id,
-                node.adj
-                    .iter()
-                    .filter(|x| !crashed_drones.contains(x))
-                    .copied()
-                    .collect::<HashSet<u8>>(),
-            );
-        }
-
-        for node in &self.nodes {
-            let mut drone_count = 0;
-
-            // Skip crashed drones during the validation phase
-            if let Drone { crashed: true, .. } = node.kind {
-                continue; // Ignore crashed drones
-            }
-
-            for &neighbor in graph.get(&node.id).unwrap() {
-                match node.kind {
-                    Client => {
-                        // Rule 2 & 3: Clients can only connect to 1-2 drones, not clients or servers
-                        if !drones.contains(&neighbor) {
-                            return Err("Client cannot connect to other clients or servers");
-                        }
-                        drone_count += 1;
+        // failed nodes are ignored like crashed drones
+        let ignored = |node: &NodeRepresentation| {
+            node.failure.is_some() || matches!(node.kind, Drone { crashed: true, .. })
+        };
+        let is_drone = |node: &NodeRepresentation| matches!(node.kind, Drone { .. });
+        for node in self.nodes().iter().filter(|n| !ignored(n)) {
+            let neighbors: Vec<&NodeRepresentation> = self
+                .graph
+                .neighbors(node.id)
+                .filter_map(|id| self.get_node_from_id(id))
+                .filter(|n| !ignored(n))
+                .collect();
+            match node.kind {
+                Client => {
+                    // Rule 2 & 3: Clients can only connect to 1-2 drones, not clients or servers
+                    if !neighbors.iter().all(|n| is_drone(n)) {
+                        return Err("Client cannot connect to other clients or servers");
                     }
-                    Server => {
-                        // Rule 4: Servers must connect to at least two drones, not clients or servers
-                        if !drones.contains(&neighbor) {
-                            return Err("Server cannot connect to other clients or servers");
-                        }
-                        drone_count += 1;
+                    if !(1..=2).contains(&neighbors.len()) {
+                        return Err("Client must connect to at least one and at most two drones");
                     }
-                    Drone { .. } => {}
-                }
-            }
-
-            if let Client = node.kind {
-                if !(1..=2).contains(&drone_count) {
-                    return Err("Client must connect to at least one and at most two drones");
                 }
-            }
-
-            if let Server = node.kind {
-                if drone_count < 2 {
-                    return Err("Server must connect to at least two drones");
+                Server => {
+                    // Rule 4: Servers must connect to at least two drones, not clients or servers
+                    if !neighbors.iter().all(|n| is_drone(n)) {
+                        return Err("Server cannot connect to other clients or servers");
+                    }
+                    if neighbors.len() < 2 {
+                        return Err("Server must connect to at least two drones");
+                    }
                 }
+                Drone { .. } => {}
             }
         }
 
         // Rule 6: Graph must be connected and bidirectional
-        if !Self::is_connected(&graph) {
+        if !self.is_connected(|n| !ignored(n)) {
             return Err("network must represent a connected and bidirectional graph");
         }
 
         // Rule 7: Removing clients and servers should still leave a connected graph
-
-        // Remove non-drone nodes, ignoring crashed drones
-        graph.retain(|&id, _| drones.contains(&id));
-
-        // Remove non-drone connections, ignoring crashed drones
-        for adj in graph.values_mut() {
-            adj.retain(|id| drones.contains(id));
-        }
-
-        if !Self::is_connected(&graph) {
+        if !self.is_connected(|n| !ignored(n) && is_drone(n)) {
             return Err("clients and servers should be at the edges of the network");
         }
 
         Ok(())
     }
 
-    /// private helper function to check if a given graph is connected
-    fn is_connected(graph: &HashMap<NodeId, HashSet<u8>>) -> bool {
-        if graph.is_empty() {
+    /// private helper function to check if the nodes for which `include` holds are connected
+    /// through edges between themselves
+    fn is_connected(&self, include: impl Fn(&NodeRepresentation) -> bool) -> bool {
+        let mut included = self.nodes().iter().filter(|n| include(n));
+        let Some(start) = included.next() else {
             return false;
-        }
+        };
+        let total = 1 + included.count();
 
-        let start = *graph.keys().next().unwrap();
         let mut visited = HashSet::new();
         let mut stack = vec![start];
-
         while let Some(node) = stack.pop() {
-            if visited.insert(node) {
-                if let Some(neighbors) = graph.get(&node) {
-                    for n in neighbors {
-                        if !visited.contains(n) {
-                            stack.push(*n);
+            if visited.insert(node.id) {
+                for n in self.graph.neighbors(node.id) {
+                    if let Some(n) = self.get_node_from_id(n) {
+                        if include(n) && !visited.contains(&n.id) {
+                            stack.push(n);
                         }
                     }
                 }
             }
         }
 
-        visited.len() == graph.len()
+        visited.len() == total
     }
 
     //tries to add edge, check if it results in a valid network, if it doesn't it puts network to
@@ -199,171 +191,474 @@ impl Network {
     pub fn add_edge(&mut self, from: NodeId, to: NodeId) -> Result<(), &'static str> {
         match self.add_edge_unchecked(from, to) {
             Ok(true) => Err("trying to add existing edge"),
-            Ok(false) => self
-                .is_valid()
-                .inspect_err(|_| self.remove_edge_unchecked(from, to)),
+            Ok(false) => self.is_valid().inspect_err(|_| {
+                self.graph.remove_edge(from, to);
+            }),
             Err(s) => Err(s),
         }
     }
 
-    /// removes edge, updating both `self.edges` and `node.adj`
-    fn remove_edge_unchecked(&mut self, from: NodeId, to: NodeId) {
-        self.edges.remove(&(from, to));
-        self.edges.remove(&(to, from));
+    /// adds a new drone connected to the given neighbors, if the resulting network is not valid
+    /// the drone is removed again and a string describing what was invalid is returned
+    ///
+    /// a drone without neighbors is always accepted, so that it can be connected later
+    pub fn add_drone(
+        &mut self,
+        id: NodeId,
+        pdr: f32,
+        neighbors: &[NodeId],
+    ) -> Result<(), &'static str> {
+        if self.get_node_from_id(id).is_some() {
+            return Err("a node with the same id already exists");
+        }
+        if neighbors
+            .iter()
+            .any(|n| self.get_node_from_id(*n).is_none())
+        {
+            return Err("cannot connect to a node that is not in the network");
+        }
 
-        if let Some(nodefrom_id) = self.get_mut_node_from_id(from) {
-            nodefrom_id.adj.remove(&to);
+        self.graph.add_node(NodeRepresentation::new(
+            id,
+            0,
+            0,
+            NodeKind::Drone {
+                pdr,
+                crashed: false,
+            },
+        ))?;
+
+        let mut res = Ok(());
+        for n in neighbors {
+            res = res.and(self.add_edge_unchecked(id, *n).map(|_| ()));
         }
+        if !neighbors.is_empty() {
+            res = res.and_then(|()| self.is_valid());
+        }
+        res.inspect_err(|_| {
+            self.graph.remove_node(id);
+        })
+    }
 
-        if let Some(nodeto_id) = self.get_mut_node_from_id(to) {
-            nodeto_id.adj.remove(&from);
+    /// converts the network to a configuration that can be used to start a new simulation with
+    /// the same topology, crashed drones are left out
+    pub fn to_config(&self) -> Config {
+        let mut cfg = Config {
+            drone: Vec::new(),
+            client: Vec::new(),
+            server: Vec::new(),
+        };
+        for n in self.nodes() {
+            let adj: Vec<NodeId> = self.graph.neighbors(n.id).collect();
+            match n.kind {
+                NodeKind::Drone {
+                    pdr,
+                    crashed: false,
+                } => cfg.drone.push(Drone {
+                    id: n.id,
+                    connected_node_ids: adj,
+                    pdr,
+                }),
+                NodeKind::Drone { crashed: true, .. } => {}
+                NodeKind::Client => cfg.client.push(Client {
+                    id: n.id,
+                    connected_drone_ids: adj,
+                }),
+                NodeKind::Server => cfg.server.push(Server {
+                    id: n.id,
+                    connected_drone_ids: adj,
+                }),
+            }
         }
+        cfg
+    }
+
+    /// adds an edge from `from` to each of the targets, if any of them can't be added or the
+    /// resulting network is not valid, none of them is added
+    pub fn add_edges(&mut self, from: NodeId, targets: &[NodeId]) -> Result<(), &'static str> {
+        let mut added = Vec::new();
+        let mut res = Ok(());
+        for to in targets {
+            match self.add_edge_unchecked(from, *to) {
+                Ok(false) => added.push(*to),
+                Ok(true) => res = Err("trying to add existing edge"),
+                Err(s) => res = Err(s),
+            }
+            if res.is_err() {
+                break;
+            }
+        }
+        res.and_then(|()| self.is_valid()).inspect_err(|_| {
+            for to in added {
+                self.graph.remove_edge(from, to);
+            }
+        })
     }
 
-    /// adds a new edge, updating both `self.edges` and `node.adj`, returns Ok(true) if it has been
+    /// adds a new edge between two nodes that are in the network, returns Ok(true) if it has been
     /// asked to add an already existing edge
-    /// # Panics
-    /// panics if it can't find the `NodeRepresentation` for the given `from` and `to` ids
     fn add_edge_unchecked(&mut self, from: NodeId, to: NodeId) -> Result<bool, &'static str> {
-        if from == to {
-            return Err("cannot connect node to itself");
+        if [from, to].iter().any(|id| {
+            self.get_node_from_id(*id)
+                .is_some_and(|n| n.failure.is_some())
+        }) {
+            return Err("cannot connect a node that failed");
         }
+        if [from, to].iter().any(|id| {
+            self.get_node_from_id(*id)
+                .is_some_and(|n| matches!(n.kind, NodeKind::Drone { crashed: true, .. }))
+        }) {
+            return Err("cannot connect crashed drone");
+        }
+        self.graph.add_edge(from, to)
+    }
 
-        if let Some(nodefrom) = self.get_mut_node_from_id(from) {
-            if matches!(
-                nodefrom.kind,
-                NodeKind::Drone {
-                    pdr: _,
-                    crashed: true
-                }
-            ) {
-                return Err("cannot connect crashed drone");
-            }
-            nodefrom.adj.insert(to as NodeId);
-        } else {
-            unreachable!("cannot find noderepr for `from` node: #{from}")
+    /// updates existing edge, with the last packet that has traveled on it
+    pub fn update_edge_activity(&mut self, from: NodeId, to: NodeId, packet_passed: PacketType) {
+        if let Some(edge) = self.graph.edge_mut(from, to) {
+            self.edge_stats
+                .entry(edge_key(from, to))
+                .or_default()
+                .add(&packet_passed);
+            edge.last_packet = Some((packet_passed, Instant::now()));
         }
+    }
 
-        if let Some(nodeto) = self.get_mut_node_from_id(to) {
-            if matches!(
-                nodeto.kind,
-                NodeKind::Drone {
-                    pdr: _,
-                    crashed: true
-                }
-            ) {
-                return Err("cannot connect crashed drone");
-            }
-            nodeto.adj.insert(from as NodeId);
-        } else {
-            unreachable!("cannot find noderepr for `to` node: #{to}")
+    /// the nodes and the edges between them
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn nodes(&self) -> &NodeStore {
+        self.graph.nodes()
+    }
+
+    /// the nodes can be changed but not added or removed
+    pub fn nodes_mut(&mut self) -> &mut NodeStore {
+        self.graph.nodes_mut()
+    }
+
+    /// if present returns immutable reference to the drone at the given `idx` of the nodes list
+    pub fn get_node_from_pos(&self, idx: usize) -> Option<&NodeRepresentation> {
+        self.nodes().get(idx)
+    }
+
+    /// if present returns immutable reference to the drone with the corresponding `id`
+    pub fn get_node_from_id(&self, id: NodeId) -> Option<&NodeRepresentation> {
+        self.nodes().get_by_id(id)
+    }
+
+    /// if present returns mutable reference to the drone with the corresponding `id`
+    pub fn get_mut_node_from_id(&mut self, id: NodeId) -> Option<&mut NodeRepresentation> {
+        self.nodes_mut().get_mut_by_id(id)
+    }
+
+    /// brings back a crashed or failed drone connected to the given neighbors, if the resulting
+    /// network is not valid the drone is put back to how it was and a string describing what was
+    /// invalid is returned, otherwise what was measured on the drone before is reset
+    pub fn revive_drone(&mut self, id: NodeId, neighbors: &[NodeId]) -> Result<(), &'static str> {
+        let Some(drone) = self.get_mut_node_from_id(id) else {
+            return Err("cannot revive a node that is not in the network");
+        };
+        let NodeKind::Drone { pdr, crashed: true } = drone.kind else {
+            return Err("only crashed drones can be revived");
+        };
+        if neighbors
+            .iter()
+            .any(|n| self.get_node_from_id(*n).is_none())
+        {
+            return Err("cannot connect to a node that is not in the network");
         }
 
-        let mut existed_already = false;
-        if self.edges.contains_key(&(from, to)) || self.edges.contains_key(&(to, from)) {
-            existed_already = true;
+        let drone = self.get_mut_node_from_id(id).expect("drone was just found");
+        let failure = drone.failure.take();
+        drone.kind = NodeKind::Drone {
+            pdr,
+            crashed: false,
+        };
+        let detached = self.graph.reattach(id);
+
+        let mut res = Ok(());
+        for n in neighbors {
+            res = res.and(self.add_edge_unchecked(id, *n).map(|_| ()));
         }
-        match from.cmp(&to) {
-            std::cmp::Ordering::Less => {
-                self.edges.insert((from, to), None);
+        res = res.and_then(|()| self.is_valid());
+        res.inspect_err(|_| {
+            self.graph.remove_edges_of(id);
+            if let Some(drone) = self.get_mut_node_from_id(id) {
+                drone.kind = NodeKind::Drone { pdr, crashed: true };
+                drone.failure = failure;
             }
-            std::cmp::Ordering::Greater => {
-                self.edges.insert((to, from), None);
+            if let Some(detached) = detached {
+                self.graph.detach(id, detached);
             }
-            // node can't have edge that points to itself
-            std::cmp::Ordering::Equal => unreachable!(),
-        };
-
-        Ok(existed_already)
+        })?;
+        if let Some(drone) = self.get_mut_node_from_id(id) {
+            drone.reset_measurements(Instant::now());
+        }
+        Ok(())
     }
 
-    /// updates existing edge, with the last packet that has traveled on it
-    pub fn update_edge_activity(&mut self, from: NodeId, to: NodeId, packet_passed: PacketType) {
-        match from.cmp(&to) {
-            std::cmp::Ordering::Less => {
-                if self.edges.contains_key(&(from, to)) {
-                    self.edges
-                        .insert((from, to), Some((packet_passed, Instant::now())));
+    /// shortest path from `from` to `to` through the current edges, both included, every node in
+    /// between is a drone, none of them has failed or crashed, returns None if there is no such
+    /// path
+    pub fn route(&self, from: NodeId, to: NodeId) -> Option<Vec<NodeId>> {
+        let alive = |id: &NodeId| self.is_up(*id);
+        if !alive(&from) || !alive(&to) {
+            return None;
+        }
+
+        let mut previous: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![to];
+                while let Some(p) = previous.get(path.last()?) {
+                    path.push(*p);
                 }
+                path.reverse();
+                return Some(path);
             }
-            std::cmp::Ordering::Greater => {
-                if self.edges.contains_key(&(to, from)) {
-                    self.edges
-                        .insert((to, from), Some((packet_passed, Instant::now())));
+            // only drones can forward packets
+            let node = self.get_node_from_id(current)?;
+            if current != from && !matches!(node.kind, NodeKind::Drone { .. }) {
+                continue;
+            }
+            for n in self.graph.neighbors(current).filter(alive) {
+                if n != from && !previous.contains_key(&n) {
+                    previous.insert(n, current);
+                    queue.push_back(n);
                 }
             }
-            // node can't have edge that points to itself
-            std::cmp::Ordering::Equal => {}
-        };
+        }
+        None
     }
 
-    /// if present returns immutable reference to the drone at the given `idx` of the nodes vector
-    pub fn get_node_from_pos(&self, idx: usize) -> Option<&NodeRepresentation> {
-        if idx < self.nodes.len() {
-            Some(&self.nodes[idx])
-        } else {
-            None
+    /// for each hop of the route, whether it can still be taken: the first hop must be a node that
+    /// has not failed or crashed, every other hop must also be connected to the previous one
+    pub fn route_hops_valid(&self, hops: &[NodeId]) -> Vec<bool> {
+        hops.iter()
+            .enumerate()
+            .map(|(i, id)| self.is_up(*id) && (i == 0 || self.graph.has_edge(hops[i - 1], *id)))
+            .collect()
+    }
+
+    /// whether the node can still take part in a route: it exists, it has not failed and it is
+    /// not a crashed drone
+    fn is_up(&self, id: NodeId) -> bool {
+        self.get_node_from_id(id).is_some_and(|n| {
+            n.failure.is_none() && !matches!(n.kind, NodeKind::Drone { crashed: true, .. })
+        })
+    }
+
+    /// the packet of the row selected in a table of the detail window of node `id`, for the
+    /// tables of messages it is the newest packet sent with the same session id
+    pub fn selected_packet(&self, id: NodeId, tab: usize, selected: usize) -> Option<&Packet> {
+        let node = self.get_node_from_id(id)?;
+        let session = |source, session_id| self.last_packet_of_session(source, session_id);
+        match (tab, node.kind) {
+            (0, _) => node.sent.get(selected),
+            (1, NodeKind::Drone { .. }) => node.dropped.get(selected),
+            (2, NodeKind::Drone { .. }) => node.shortcutted.get(selected),
+            (1, _) => {
+                let idx = node.msent.len().checked_sub(selected + 1)?;
+                let (_, (message, _)) = node.msent.get_index(idx)?;
+                session(id, message.session_id)
+            }
+            (2, _) => {
+                let message = node.mreceived.get(selected)?;
+                session(message.source, message.session_id)
+            }
+            _ => None,
         }
     }
 
-    /// if present returns immutable reference to the drone with the corresponding `id`
-    pub fn get_node_from_id(&self, id: NodeId) -> Option<&NodeRepresentation> {
-        self.nodes.iter().find(|&node| node.id == id)
+    /// the newest packet of the session that its source still keeps in memory
+    pub fn last_packet_of_session(&self, source: NodeId, session_id: u64) -> Option<&Packet> {
+        self.get_node_from_id(source)?
+            .sent
+            .iter()
+            .find(|p| p.session_id == session_id)
     }
 
-    /// if present returns mutable reference to the drone with the corresponding `id`
-    pub fn get_mut_node_from_id(&mut self, id: NodeId) -> Option<&mut NodeRepresentation> {
-        self.nodes.iter_mut().find(|node| node.id == id)
+    /// adds a fragment sent by `source` to its session, together with the message the source
+    /// started sending in that session, if it is still kept
+    pub fn record_fragment(
+        &mut self,
+        source: NodeId,
+        session_id: u64,
+        fragment: &Fragment,
+        decoder: Option<PayloadDecoder>,
+    ) {
+        let sent = self
+            .graph
+            .nodes()
+            .get_by_id(source)
+            .and_then(|n| n.msent.get(&session_id))
+            .map(|(message, _)| message);
+        self.sessions
+            .record_fragment(source, session_id, fragment, sent, decoder);
     }
 
-    /// sets drone with matching id `crashed` parameter to true,
-    /// then removes all edges that contain the drone
-    pub fn crash_drone(&mut self, id: NodeId) -> Result<(), &'static str> {
+    /// marks the node as failed with the given reason, a failed drone is also crashed, then
+    /// detaches it from its neighbors keeping its edges as failed ones, returns the neighbors it
+    /// had
+    ///
+    /// the network is not validated, the node is already gone
+    pub fn fail_node(&mut self, id: NodeId, reason: String) -> Vec<NodeId> {
+        let Some(node) = self.get_mut_node_from_id(id) else {
+            unreachable!("node that failed: #{id} not present in network")
+        };
+        node.failure = Some(reason);
+        if let NodeKind::Drone { pdr, .. } = node.kind {
+            node.kind = NodeKind::Drone { pdr, crashed: true };
+        }
+        let neighbors: Vec<NodeId> = self
+            .graph
+            .remove_edges_of(id)
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        self.graph.detach(
+            id,
+            Detached {
+                neighbors: neighbors.iter().copied().collect(),
+                failed: true,
+            },
+        );
+        neighbors
+    }
+
+    /// sets drones with matching ids `crashed` parameter to true, then removes all edges that
+    /// contain the drones, the network is validated only after all of them have been crashed, if
+    /// it is not valid every drone is put back to how it was before
+    pub fn crash_drones(&mut self, ids: &[NodeId]) -> Result<(), &'static str> {
         // ---------------------------------------------------------------
-        // borrow the drone to change it to crashed, and save how it was before
+        // check that all the nodes can be crashed before changing anything
         // ---------------------------------------------------------------
-        let Some(drone) = self.nodes.iter_mut().find(|node| node.id == id) else {
-            unreachable!("node to crash: #{id} not present in network")
-        };
-        let oldkind = drone.kind;
-        drone.kind = match drone.kind {
-            NodeKind::Drone {
-                pdr,
-                crashed: false,
-            } => NodeKind::Drone { pdr, crashed: true },
-            NodeKind::Drone { crashed: true, .. } => {
-                return Err("trying to crash already crashed drone")
+        for id in ids {
+            let Some(drone) = self.get_node_from_id(*id) else {
+                unreachable!("node to crash: #{id} not present in network")
+            };
+            match drone.kind {
+                NodeKind::Drone { crashed: false, .. } => {}
+                NodeKind::Drone { crashed: true, .. } => {
+                    return Err("trying to crash already crashed drone")
+                }
+                _ => return Err("trying to crash a node that is not a drone"),
             }
-            _ => unreachable!("trying to crash a node that is not a drone"),
-        };
+        }
 
         // ---------------------------------------------------------------
-        // borrow edges (drone went out of scope because its no longer used so you can borrow again) and filter out the edges that will be removed
+        // change the drones to crashed, and save how they were before
         // ---------------------------------------------------------------
-        let edges = self.edges.borrow_mut();
-        let oldedges = edges.clone();
-
-        edges.retain(|(from, to), _| *from != id && *to != id);
+        let mut oldkinds = Vec::new();
+        for id in ids {
+            if let Some(drone) = self.get_mut_node_from_id(*id) {
+                oldkinds.push((*id, drone.kind));
+                if let NodeKind::Drone { pdr, .. } = drone.kind {
+                    drone.kind = NodeKind::Drone { pdr, crashed: true };
+                }
+            }
+        }
 
         // ---------------------------------------------------------------
-        // check if the modified network is valid (can borrow because edges and drone have been dropped)
+        // remove the edges of the drones, keeping them to put them back
         // ---------------------------------------------------------------
-        let res = self.is_valid();
+        let mut oldedges = Vec::new();
+        for id in ids {
+            for (n, edge) in self.graph.remove_edges_of(*id) {
+                oldedges.push((*id, n, edge));
+            }
+        }
 
         // ---------------------------------------------------------------
-        // borrow one last time drone and edges to fix stuff back to how it was before in case
-        // network is not valid after changes
+        // check if the modified network is valid, in case it is not fix stuff back to how it was
+        // before
         // ---------------------------------------------------------------
-        match res {
-            Ok(()) => Ok(()),
+        match self.is_valid() {
+            Ok(()) => {
+                for id in ids {
+                    let neighbors = oldedges
+                        .iter()
+                        .filter_map(|(a, b, _)| match (*a == *id, *b == *id) {
+                            (true, _) => Some(*b),
+                            (_, true) => Some(*a),
+                            _ => None,
+                        })
+                        .collect();
+                    self.graph.detach(
+                        *id,
+                        Detached {
+                            neighbors,
+                            failed: false,
+                        },
+                    );
+                }
+                Ok(())
+            }
             Err(s) => {
-                let Some(drone) = self.nodes.iter_mut().find(|node| node.id == id) else {
-                    unreachable!("node to crash: #{id} not present in network")
-                };
-                drone.kind = oldkind;
-                self.edges = oldedges;
+                for (id, oldkind) in oldkinds {
+                    if let Some(drone) = self.get_mut_node_from_id(id) {
+                        drone.kind = oldkind;
+                    }
+                }
+                for (a, b, edge) in oldedges {
+                    let _ = self.graph.insert_edge(a, b, edge);
+                }
                 Err(s)
             }
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// the network of `tests/config_files/line.toml`: drones 1 to 4 in a line, client 5
+    /// connected to 4 and server 6 connected to 4 and 3
+    pub(crate) fn line_network() -> Network {
+        let config = toml::from_str(
+            &std::fs::read_to_string("./tests/config_files/line.toml")
+                .expect("Unable to read config file"),
+        )
+        .expect("Unable to parse TOML");
+        Network::new(&config).unwrap()
+    }
+
+    #[test]
+    fn route() {
+        let mut network = line_network();
+        assert_eq!(network.route(1, 5), Some(vec![1, 2, 3, 4, 5]));
+        assert_eq!(network.route(2, 6), Some(vec![2, 3, 6]));
+        // clients and servers do not forward packets
+        assert_eq!(network.route(6, 5), Some(vec![6, 4, 5]));
+        network.crash_drones(&[1]).unwrap();
+        assert_eq!(network.route(1, 5), None);
+        assert_eq!(network.route(5, 1), None);
+        // a failed node is not an endpoint either, even of a route to itself
+        network.fail_node(5, "disconnected".to_string());
+        assert_eq!(network.route(6, 5), None);
+        assert_eq!(network.route(5, 5), None);
+    }
+
+    #[test]
+    fn revive_resets_measurements() {
+        let mut network = line_network();
+        network.crash_drones(&[1]).unwrap();
+        let drone = network.get_mut_node_from_id(1).unwrap();
+        drone.stats.dropped.fragment = 3;
+        drone.drop_check.record_non_fragment_drop();
+        drone.conformance.checked = 4;
+
+        // an invalid revive leaves the drone as it was
+        assert!(network.revive_drone(1, &[9]).is_err());
+        assert_eq!(network.get_node_from_id(1).unwrap().conformance.checked, 4);
+        network.revive_drone(1, &[2]).unwrap();
+        let drone = network.get_node_from_id(1).unwrap();
+        assert_eq!(drone.stats.dropped.fragment, 0);
+        assert_eq!(drone.conformance.checked, 0);
+        assert!(!drone.drop_check.is_suspicious());
+    }
+}
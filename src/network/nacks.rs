@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+
+use wg_2024::{
+    network::NodeId,
+    packet::{NackType, Packet, PacketType},
+};
+
+/// nack packets that are remembered for the drill-down, older ones are forgotten
+pub const KEPT_NACKS: usize = 500;
+
+/// nacks of each type, the ones that carry a node are counted per node
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NackCounts {
+    /// by the node the packet could not be forwarded to
+    pub error_in_routing: BTreeMap<NodeId, u64>,
+    pub destination_is_drone: u64,
+    pub dropped: u64,
+    /// by the node the packet was not meant for
+    pub unexpected_recipient: BTreeMap<NodeId, u64>,
+}
+
+impl NackCounts {
+    fn add(&mut self, nack_type: NackType) {
+        match nack_type {
+            NackType::ErrorInRouting(id) => *self.error_in_routing.entry(id).or_default() += 1,
+            NackType::DestinationIsDrone => self.destination_is_drone += 1,
+            NackType::Dropped => self.dropped += 1,
+            NackType::UnexpectedRecipient(id) => {
+                *self.unexpected_recipient.entry(id).or_default() += 1;
+            }
+        }
+    }
+
+    pub fn error_in_routing_total(&self) -> u64 {
+        self.error_in_routing.values().sum()
+    }
+
+    pub fn unexpected_recipient_total(&self) -> u64 {
+        self.unexpected_recipient.values().sum()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.error_in_routing_total()
+            + self.destination_is_drone
+            + self.dropped
+            + self.unexpected_recipient_total()
+    }
+}
+
+/// how the nacks are grouped in the dashboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NackGrouping {
+    /// by the node that created the nack
+    Emitter,
+    /// by the session of the nacked packet, that is the destination of the nack
+    Session,
+}
+
+/// a row of the dashboard, session ids are only unique for the node that started the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NackGroup {
+    Emitter(NodeId),
+    Session { source: NodeId, session_id: u64 },
+}
+
+impl Display for NackGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NackGroup::Emitter(id) => write!(f, "#{id}"),
+            NackGroup::Session { source, session_id } => write!(f, "{session_id} of #{source}"),
+        }
+    }
+}
+
+/// nacks created in the network, grouped by the node that created them and by session
+#[derive(Debug, Default)]
+pub struct Nacks {
+    pub total: NackCounts,
+    pub by_emitter: BTreeMap<NodeId, NackCounts>,
+    /// by the source of the session and its id
+    pub by_session: BTreeMap<(NodeId, u64), NackCounts>,
+    /// emitter and packet of the last nacks, newest first
+    pub recent: VecDeque<(NodeId, Packet)>,
+}
+
+impl Nacks {
+    /// counts the packet if it is a nack sent by `sender` and `sender` created it, which is when it
+    /// is the first hop of the route, the nacks forwarded by the other nodes are not counted again
+    pub fn record(&mut self, sender: NodeId, packet: &Packet) {
+        let PacketType::Nack(nack) = &packet.pack_type else {
+            return;
+        };
+        if packet.routing_header.hops.first() != Some(&sender) {
+            return;
+        }
+        self.total.add(nack.nack_type);
+        self.by_emitter
+            .entry(sender)
+            .or_default()
+            .add(nack.nack_type);
+        // the nack goes back to the node that started the session
+        if let Some(&source) = packet.routing_header.hops.last() {
+            self.by_session
+                .entry((source, packet.session_id))
+                .or_default()
+                .add(nack.nack_type);
+        }
+        self.recent.push_front((sender, packet.clone()));
+        self.recent.truncate(KEPT_NACKS);
+    }
+
+    /// rows of the dashboard for the given grouping
+    pub fn groups(&self, grouping: NackGrouping) -> Vec<(NackGroup, &NackCounts)> {
+        match grouping {
+            NackGrouping::Emitter => self
+                .by_emitter
+                .iter()
+                .map(|(id, counts)| (NackGroup::Emitter(*id), counts))
+                .collect(),
+            NackGrouping::Session => self
+                .by_session
+                .iter()
+                .map(|(&(source, session_id), counts)| {
+                    (NackGroup::Session { source, session_id }, counts)
+                })
+                .collect(),
+        }
+    }
+
+    /// the remembered nacks of the group, newest first
+    pub fn packets_of(&self, group: NackGroup) -> impl Iterator<Item = &(NodeId, Packet)> + '_ {
+        self.recent
+            .iter()
+            .filter(move |(emitter, packet)| match group {
+                NackGroup::Emitter(id) => *emitter == id,
+                NackGroup::Session { source, session_id } => {
+                    packet.session_id == session_id
+                        && packet.routing_header.hops.last() == Some(&source)
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wg_2024::{network::SourceRoutingHeader, packet::Nack};
+
+    use super::*;
+
+    fn nack(nack_type: NackType, hops: Vec<NodeId>, session_id: u64) -> Packet {
+        Packet {
+            pack_type: PacketType::Nack(Nack {
+                fragment_index: 0,
+                nack_type,
+            }),
+            routing_header: SourceRoutingHeader { hop_index: 1, hops },
+            session_id,
+        }
+    }
+
+    #[test]
+    fn breakdown() {
+        let mut nacks = Nacks::default();
+        let routing = nack(NackType::ErrorInRouting(3), vec![2, 1, 5], 7);
+        nacks.record(2, &routing);
+        // forwarded by 1, not counted again
+        nacks.record(1, &routing);
+        nacks.record(2, &nack(NackType::ErrorInRouting(3), vec![2, 1, 5], 8));
+        nacks.record(1, &nack(NackType::Dropped, vec![1, 5], 7));
+        nacks.record(4, &nack(NackType::UnexpectedRecipient(9), vec![4, 6], 8));
+        // the same session id started by another node
+        nacks.record(4, &nack(NackType::Dropped, vec![4, 5], 8));
+
+        assert_eq!(nacks.total.total(), 5);
+        assert_eq!(nacks.total.error_in_routing, BTreeMap::from([(3, 2)]));
+        assert_eq!(nacks.by_emitter[&2].error_in_routing_total(), 2);
+        assert_eq!(nacks.by_emitter[&1].dropped, 1);
+        assert_eq!(nacks.by_session[&(6, 8)].unexpected_recipient_total(), 1);
+        assert_eq!(nacks.by_session[&(5, 8)].total(), 2);
+        assert_eq!(nacks.groups(NackGrouping::Emitter).len(), 3);
+        assert_eq!(nacks.groups(NackGrouping::Session).len(), 3);
+        let session = |source, session_id| NackGroup::Session { source, session_id };
+        assert_eq!(nacks.packets_of(session(5, 7)).count(), 2);
+        assert_eq!(nacks.packets_of(session(5, 8)).count(), 2);
+        assert_eq!(nacks.packets_of(session(6, 8)).count(), 1);
+        assert_eq!(nacks.packets_of(NackGroup::Emitter(2)).count(), 2);
+    }
+}
@@ -1,6 +1,9 @@
 // use std::hash::Hash;
 
-use std::collections::{HashSet, VecDeque};
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Instant,
+};
 
 use indexmap::IndexMap;
 use messages::{node_event::EventNetworkGraph, Message};
@@ -10,7 +13,11 @@ use wg_2024::{
     packet::Packet,
 };
 
+use super::conformance::Conformance;
+use super::drop_check::DropCheck;
+use super::drop_window::DropWindow;
 use super::node_kind::NodeKind;
+use super::node_stats::NodeStats;
 
 #[derive(Debug, Clone)]
 pub struct NodeRepresentation {
@@ -18,19 +25,28 @@ pub struct NodeRepresentation {
     pub x: u32,
     pub y: u32,
     pub kind: NodeKind,
-    pub adj: HashSet<NodeId>,
     // all nodes
     pub sent: VecDeque<Packet>,
     pub n_frags_sent: u64,
     pub thread_name: String,
+    /// set when the thread of the node exited without being crashed, with its panic payload
+    pub failure: Option<String>,
     // drone
     pub dropped: VecDeque<Packet>,
     pub n_frags_dropped: u64,
+    pub drop_window: DropWindow,
+    /// checks that the drone drops only fragments, as often as its pdr says
+    pub drop_check: DropCheck,
+    /// events of the drone checked against the protocol and the rules it broke
+    pub conformance: Conformance,
     pub shortcutted: VecDeque<Packet>,
     // client and server
     pub msent: IndexMap<u64, (Message, bool)>,
+    /// sessions of the messages moved out of `msent` before they were sent successfully
+    pub msent_evicted: HashSet<u64>,
     pub mreceived: VecDeque<Message>,
     pub knowntopology: EventNetworkGraph,
+    pub stats: NodeStats,
 }
 
 impl PartialEq for NodeRepresentation {
@@ -49,27 +65,44 @@ impl std::hash::Hash for NodeRepresentation {
 }
 
 impl NodeRepresentation {
-    pub fn new(id: NodeId, x: u32, y: u32, kind: NodeKind, adj: HashSet<NodeId>) -> Self {
+    pub fn new(id: NodeId, x: u32, y: u32, kind: NodeKind) -> Self {
         //let s = format!("{:?} #{}", kind, id);
         NodeRepresentation {
             id,
             x,
             y,
             kind,
-            adj,
             thread_name: String::default(),
+            failure: None,
             sent: VecDeque::new(),
             n_frags_sent: 0,
             dropped: VecDeque::new(),
             n_frags_dropped: 0,
+            drop_window: DropWindow::new(Instant::now()),
+            drop_check: DropCheck::default(),
+            conformance: Conformance::default(),
             shortcutted: VecDeque::new(),
             msent: IndexMap::new(),
+            msent_evicted: HashSet::new(),
             mreceived: VecDeque::new(),
             knowntopology: EventNetworkGraph { nodes: Vec::new() },
+            stats: NodeStats::default(),
         }
     }
 
+    /// forgets what was measured on the drone before it crashed, so that a revived drone is
+    /// judged only on what it does after `now`
+    pub fn reset_measurements(&mut self, now: Instant) {
+        self.stats = NodeStats::default();
+        self.drop_window = DropWindow::new(now);
+        self.drop_check = DropCheck::default();
+        self.conformance = Conformance::default();
+    }
+
     pub fn short_label(&self) -> String {
+        if self.failure.is_some() {
+            return "(!)".to_owned();
+        }
         match self.kind {
             NodeKind::Drone { pdr: _, crashed } => {
                 if crashed {
@@ -83,6 +116,9 @@ impl NodeRepresentation {
         }
     }
     pub fn color(&self) -> ratatui::prelude::Color {
+        if self.failure.is_some() {
+            return crate::utilities::theme::FAILED_COLOR;
+        }
         match self.kind {
             NodeKind::Drone { pdr: _, crashed } => {
                 if crashed {
@@ -105,7 +141,6 @@ impl NodeRepresentation {
                 pdr: d.pdr,
                 crashed: false,
             },
-            d.connected_node_ids.iter().copied().collect(),
         )
     }
 
@@ -115,7 +150,6 @@ impl NodeRepresentation {
             u32::from(d.id) * 5 + 5,
             u32::from(d.id) % 3 * 5 + 5,
             NodeKind::Client,
-            d.connected_drone_ids.iter().copied().collect(),
         )
     }
 
@@ -125,7 +159,6 @@ impl NodeRepresentation {
             u32::from(d.id) * 5 + 5,
             u32::from(d.id) % 3 * 5 + 5,
             NodeKind::Server,
-            d.connected_drone_ids.iter().copied().collect(),
         )
     }
 
@@ -154,9 +187,9 @@ mod tests {
 
     #[test]
     fn test_partial_eq() {
-        let node1 = NodeRepresentation::new(1, 10, 20, NodeKind::Client, HashSet::new());
-        let node2 = NodeRepresentation::new(1, 30, 40, NodeKind::Server, HashSet::new());
-        let node3 = NodeRepresentation::new(2, 10, 20, NodeKind::Client, HashSet::new());
+        let node1 = NodeRepresentation::new(1, 10, 20, NodeKind::Client);
+        let node2 = NodeRepresentation::new(1, 30, 40, NodeKind::Server);
+        let node3 = NodeRepresentation::new(2, 10, 20, NodeKind::Client);
 
         assert_eq!(node1, node2); // Same ID, should be equal
         assert_ne!(node1, node3); // Different ID, should not be equal
@@ -164,9 +197,9 @@ mod tests {
 
     #[test]
     fn test_hash() {
-        let node1 = NodeRepresentation::new(1, 10, 20, NodeKind::Client, HashSet::new());
-        let node2 = NodeRepresentation::new(1, 30, 40, NodeKind::Server, HashSet::new());
-        let node3 = NodeRepresentation::new(2, 10, 20, NodeKind::Client, HashSet::new());
+        let node1 = NodeRepresentation::new(1, 10, 20, NodeKind::Client);
+        let node2 = NodeRepresentation::new(1, 30, 40, NodeKind::Server);
+        let node3 = NodeRepresentation::new(2, 10, 20, NodeKind::Client);
 
         let mut hasher1 = DefaultHasher::new();
         node1.hash(&mut hasher1);
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use messages::{ErrorType, MessageType, RequestType, ResponseType};
+use serde::Serialize;
+use wg_2024::packet::PacketType;
+
+/// number of packets seen for each packet type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PacketCounts {
+    pub fragment: u64,
+    pub ack: u64,
+    pub nack: u64,
+    pub flood_request: u64,
+    pub flood_response: u64,
+}
+
+impl PacketCounts {
+    pub fn add(&mut self, pack_type: &PacketType) {
+        let counter = match pack_type {
+            PacketType::MsgFragment(_) => &mut self.fragment,
+            PacketType::Ack(_) => &mut self.ack,
+            PacketType::Nack(_) => &mut self.nack,
+            PacketType::FloodRequest(_) => &mut self.flood_request,
+            PacketType::FloodResponse(_) => &mut self.flood_response,
+        };
+        *counter = counter.saturating_add(1);
+    }
+}
+
+/// number of messages seen for each kind, see `message_kind`
+pub type MessageCounts = BTreeMap<&'static str, u64>;
+
+/// counters of everything a node did since the start of the simulation, unlike the packets and
+/// messages kept in `NodeRepresentation` they are never discarded
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeStats {
+    pub sent: PacketCounts,
+    pub dropped: PacketCounts,
+    pub shortcutted: PacketCounts,
+    pub messages_started: MessageCounts,
+    pub messages_completed: MessageCounts,
+    pub messages_received: MessageCounts,
+}
+
+/// increments the counter of the kind of the given message
+pub fn count_message(counts: &mut MessageCounts, content: &MessageType) {
+    let counter = counts.entry(message_kind(content)).or_default();
+    *counter = counter.saturating_add(1);
+}
+
+/// short name for the kind of a message, e.g. "request/text"
+pub fn message_kind(content: &MessageType) -> &'static str {
+    match content {
+        MessageType::Request(r) => match r {
+            RequestType::TextRequest(_) => "request/text",
+            RequestType::MediaRequest(_) => "request/media",
+            RequestType::ChatRequest(_) => "request/chat",
+            RequestType::DiscoveryRequest(()) => "request/discovery",
+        },
+        MessageType::Response(r) => match r {
+            ResponseType::TextResponse(_) => "response/text",
+            ResponseType::MediaResponse(_) => "response/media",
+            ResponseType::ChatResponse(_) => "response/chat",
+            ResponseType::DiscoveryResponse(_) => "response/discovery",
+        },
+        MessageType::Error(e) => match e {
+            ErrorType::Unsupported(_) => "error/unsupported",
+            ErrorType::Unexpected(_) => "error/unexpected",
+            ErrorType::Unregistered(_) => "error/unregistered",
+        },
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+use wg_2024::network::NodeId;
+
+use super::node_representation::NodeRepresentation;
+
+/// nodes of the network in the order they are listed, with an index from id to position so that
+/// looking a node up by id does not scan the list
+///
+/// the id of a node must not be changed through `iter_mut` or `IndexMut`, otherwise the index no
+/// longer matches the list
+#[derive(Debug, Default)]
+pub struct NodeStore {
+    nodes: Vec<NodeRepresentation>,
+    positions: HashMap<NodeId, usize>,
+}
+
+impl NodeStore {
+    /// appends the node at the end of the list, fails if a node with the same id is already there
+    pub(super) fn push(&mut self, node: NodeRepresentation) -> Result<(), &'static str> {
+        if self.positions.contains_key(&node.id) {
+            return Err("Duplicate node_id found in Network Initialization File");
+        }
+        self.positions.insert(node.id, self.nodes.len());
+        self.nodes.push(node);
+        Ok(())
+    }
+
+    /// removes the node keeping the order of the others, the positions after it shift back by one
+    pub(super) fn remove(&mut self, id: NodeId) -> Option<NodeRepresentation> {
+        let pos = self.positions.remove(&id)?;
+        let node = self.nodes.remove(pos);
+        for n in &self.nodes[pos..] {
+            if let Some(p) = self.positions.get_mut(&n.id) {
+                *p -= 1;
+            }
+        }
+        Some(node)
+    }
+
+    /// position of the node in the list
+    pub fn position(&self, id: NodeId) -> Option<usize> {
+        self.positions.get(&id).copied()
+    }
+
+    pub fn get(&self, pos: usize) -> Option<&NodeRepresentation> {
+        self.nodes.get(pos)
+    }
+
+    pub fn get_by_id(&self, id: NodeId) -> Option<&NodeRepresentation> {
+        let node = &self.nodes[self.position(id)?];
+        debug_assert_eq!(node.id, id, "the id of a node was changed in place");
+        Some(node)
+    }
+
+    pub fn get_mut_by_id(&mut self, id: NodeId) -> Option<&mut NodeRepresentation> {
+        let pos = self.position(id)?;
+        let node = &mut self.nodes[pos];
+        debug_assert_eq!(node.id, id, "the id of a node was changed in place");
+        Some(node)
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.positions.contains_key(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, NodeRepresentation> {
+        self.nodes.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, NodeRepresentation> {
+        self.nodes.iter_mut()
+    }
+}
+
+impl Index<usize> for NodeStore {
+    type Output = NodeRepresentation;
+
+    fn index(&self, pos: usize) -> &NodeRepresentation {
+        &self.nodes[pos]
+    }
+}
+
+impl IndexMut<usize> for NodeStore {
+    fn index_mut(&mut self, pos: usize) -> &mut NodeRepresentation {
+        &mut self.nodes[pos]
+    }
+}
+
+impl<'a> IntoIterator for &'a NodeStore {
+    type Item = &'a NodeRepresentation;
+    type IntoIter = std::slice::Iter<'a, NodeRepresentation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut NodeStore {
+    type Item = &'a mut NodeRepresentation;
+    type IntoIter = std::slice::IterMut<'a, NodeRepresentation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::node_kind::NodeKind;
+
+    fn node(id: NodeId) -> NodeRepresentation {
+        NodeRepresentation::new(id, 0, 0, NodeKind::Client)
+    }
+
+    #[test]
+    fn lookups_follow_removals() {
+        let mut store = NodeStore::default();
+        for id in [7, 3, 9, 1] {
+            store.push(node(id)).unwrap();
+        }
+        assert!(store.push(node(3)).is_err());
+        assert_eq!(store.len(), 4);
+
+        assert_eq!(store.remove(3).map(|n| n.id), Some(3));
+        assert!(store.remove(3).is_none());
+        let order: Vec<NodeId> = store.iter().map(|n| n.id).collect();
+        assert_eq!(order, vec![7, 9, 1]);
+        for (pos, id) in order.iter().enumerate() {
+            assert_eq!(store.position(*id), Some(pos));
+            assert_eq!(store.get_by_id(*id).map(|n| n.id), Some(*id));
+            assert_eq!(store.get(pos).map(|n| n.id), Some(*id));
+        }
+        assert!(store.get_by_id(3).is_none());
+        assert!(store.get(3).is_none());
+
+        store.get_mut_by_id(1).unwrap().thread_name = "one".to_string();
+        assert_eq!(store[2].thread_name, "one");
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use messages::Message;
+use wg_2024::{network::NodeId, packet::Fragment};
+
+/// sessions that are remembered, older ones are forgotten
+pub const KEPT_SESSIONS: usize = 100;
+
+/// turns the payload of a complete session into the message that was sent, the encoding is
+/// chosen by the clients and servers, so it has to be given to the sc
+pub type PayloadDecoder = fn(&[u8]) -> Result<Message, String>;
+
+/// fragments sent by a client or server for one session
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub source: NodeId,
+    pub session_id: u64,
+    pub total_n_fragments: u64,
+    /// payload of each fragment received, by index
+    fragments: BTreeMap<u64, Vec<u8>>,
+    /// indices of the fragments that were sent more than once
+    pub retransmitted: BTreeSet<u64>,
+    /// the decoded message once every fragment was sent, or why it could not be decoded
+    pub decoded: Option<Result<Message, String>>,
+    /// the message the source said it started sending in this session, taken once every
+    /// fragment was sent
+    pub sent: Option<Message>,
+}
+
+impl Session {
+    fn new(source: NodeId, session_id: u64) -> Self {
+        Session {
+            source,
+            session_id,
+            total_n_fragments: 0,
+            fragments: BTreeMap::new(),
+            retransmitted: BTreeSet::new(),
+            decoded: None,
+            sent: None,
+        }
+    }
+
+    pub fn received(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// indices of the fragments that were not sent yet
+    pub fn missing(&self) -> Vec<u64> {
+        (0..self.total_n_fragments)
+            .filter(|i| !self.fragments.contains_key(i))
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.total_n_fragments > 0 && self.missing().is_empty()
+    }
+
+    /// the payloads of the fragments joined in order
+    pub fn payload(&self) -> Vec<u8> {
+        self.fragments.values().flatten().copied().collect()
+    }
+
+    /// whether the decoded payload is the message that was sent, none if either is not known
+    pub fn matches_sent(&self) -> Option<bool> {
+        match (&self.decoded, &self.sent) {
+            (Some(Ok(decoded)), Some(sent)) => Some(decoded == sent),
+            _ => None,
+        }
+    }
+}
+
+/// sessions of the fragments sent by clients and servers, newest first
+#[derive(Debug, Default)]
+pub struct Sessions {
+    pub sessions: VecDeque<Session>,
+}
+
+impl Sessions {
+    pub fn get(&self, source: NodeId, session_id: u64) -> Option<&Session> {
+        self.sessions
+            .iter()
+            .find(|s| s.source == source && s.session_id == session_id)
+    }
+
+    /// adds a fragment sent by `source`, then if the session is complete it keeps `sent` to
+    /// compare it with the payload, which is decoded the first time if a decoder is given
+    pub fn record_fragment(
+        &mut self,
+        source: NodeId,
+        session_id: u64,
+        fragment: &Fragment,
+        sent: Option<&Message>,
+        decoder: Option<PayloadDecoder>,
+    ) {
+        let idx = match self
+            .sessions
+            .iter()
+            .position(|s| s.source == source && s.session_id == session_id)
+        {
+            Some(idx) => idx,
+            None => {
+                self.sessions.push_front(Session::new(source, session_id));
+                self.sessions.truncate(KEPT_SESSIONS);
+                0
+            }
+        };
+        let session = &mut self.sessions[idx];
+        session.total_n_fragments = fragment.total_n_fragments;
+        let length = usize::from(fragment.length).min(fragment.data.len());
+        let data = fragment.data[..length].to_vec();
+        if session
+            .fragments
+            .insert(fragment.fragment_index, data)
+            .is_some()
+        {
+            session.retransmitted.insert(fragment.fragment_index);
+        }
+        if !session.is_complete() {
+            return;
+        }
+        if session.sent.is_none() {
+            session.sent = sent.cloned();
+        }
+        if session.decoded.is_none() {
+            if let Some(decode) = decoder {
+                session.decoded = Some(decode(&session.payload()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use messages::{MessageType, RequestType, TextRequest};
+
+    use super::*;
+
+    fn fragment(fragment_index: u64, total_n_fragments: u64, text: &[u8]) -> Fragment {
+        let mut data = [0; 128];
+        data[..text.len()].copy_from_slice(text);
+        Fragment {
+            fragment_index,
+            total_n_fragments,
+            length: u8::try_from(text.len()).unwrap(),
+            data,
+        }
+    }
+
+    fn text(text: String) -> Message {
+        Message {
+            source: 5,
+            destination: 6,
+            session_id: 3,
+            content: MessageType::Request(RequestType::TextRequest(TextRequest::Text(text))),
+        }
+    }
+
+    fn decode(payload: &[u8]) -> Result<Message, String> {
+        let text = String::from_utf8(payload.to_vec()).map_err(|e| e.to_string())?;
+        Ok(self::text(text))
+    }
+
+    #[test]
+    fn reassembly() {
+        let mut sessions = Sessions::default();
+        let sent = text("hello".to_string());
+        sessions.record_fragment(5, 3, &fragment(2, 3, b"lo"), Some(&sent), Some(decode));
+        sessions.record_fragment(5, 3, &fragment(0, 3, b"he"), Some(&sent), Some(decode));
+        let session = sessions.get(5, 3).unwrap();
+        assert_eq!(session.received(), 2);
+        assert_eq!(session.missing(), vec![1]);
+        assert!(session.decoded.is_none());
+        assert!(session.sent.is_none());
+
+        sessions.record_fragment(5, 3, &fragment(0, 3, b"he"), Some(&sent), Some(decode));
+        sessions.record_fragment(5, 3, &fragment(1, 3, b"l"), Some(&sent), Some(decode));
+        let session = sessions.get(5, 3).unwrap();
+        assert!(session.is_complete());
+        assert_eq!(session.retransmitted, BTreeSet::from([0]));
+        assert_eq!(session.payload(), b"hello");
+        let Some(Ok(message)) = &session.decoded else {
+            panic!("complete session was not decoded")
+        };
+        assert_eq!(
+            message.content,
+            MessageType::Request(RequestType::TextRequest(TextRequest::Text(
+                "hello".to_string()
+            )))
+        );
+        assert_eq!(session.matches_sent(), Some(true));
+        assert!(sessions.get(6, 3).is_none());
+
+        // a fragment lost its last byte on the way
+        let sent = text("hi!".to_string());
+        sessions.record_fragment(6, 3, &fragment(0, 1, b"hi"), Some(&sent), Some(decode));
+        assert_eq!(sessions.get(6, 3).unwrap().matches_sent(), Some(false));
+        // without a decoder the sent message is still kept
+        sessions.record_fragment(7, 3, &fragment(0, 1, b"hi"), Some(&sent), None);
+        let session = sessions.get(7, 3).unwrap();
+        assert_eq!(session.sent.as_ref(), Some(&sent));
+        assert_eq!(session.matches_sent(), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
+
+use wg_2024::{
+    network::NodeId,
+    packet::{NackType, PacketType},
+};
+
+/// seconds of history kept for the charts of the metrics window
+pub const THROUGHPUT_HISTORY_SECS: u64 = 300;
+
+/// names of the nack types, in the same order as `Sample::nacks`
+pub const NACK_NAMES: [&str; 4] = [
+    "ErrorInRouting",
+    "DestinationIsDrone",
+    "Dropped",
+    "UnexpectedRecipient",
+];
+
+/// what happened during one second
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    pub fragments: u64,
+    pub acks: u64,
+    /// one counter for each nack type, see `NACK_NAMES`
+    pub nacks: [u64; 4],
+    pub flood_requests: u64,
+    pub messages_completed: u64,
+    /// sessions started and not completed yet, at the end of the second
+    pub active_sessions: u64,
+}
+
+/// per second history of a node or of the whole network
+#[derive(Debug, Default)]
+struct History {
+    samples: VecDeque<(u64, Sample)>,
+    active_sessions: u64,
+}
+
+impl History {
+    /// returns the sample of the given second, creating it if needed, the active sessions are
+    /// carried over from the previous sample
+    fn at(&mut self, sec: u64) -> &mut Sample {
+        if self.samples.back().is_none_or(|(s, _)| *s != sec) {
+            self.samples.push_back((
+                sec,
+                Sample {
+                    active_sessions: self.active_sessions,
+                    ..Sample::default()
+                },
+            ));
+            while self
+                .samples
+                .front()
+                .is_some_and(|(s, _)| s + THROUGHPUT_HISTORY_SECS < sec)
+            {
+                self.samples.pop_front();
+            }
+        }
+        &mut self.samples.back_mut().expect("sample was just pushed").1
+    }
+
+    fn series(&self, now: u64, len: u64) -> Vec<Sample> {
+        let start = now.saturating_sub(len.saturating_sub(1));
+        let mut samples = self.samples.iter().peekable();
+        let mut active = 0;
+        // seconds before the start of the simulation are empty
+        let before = usize::try_from(len.saturating_sub(now - start + 1)).unwrap_or_default();
+        let mut series = vec![Sample::default(); before];
+        for sec in start..=now {
+            // skip older samples, keeping their active sessions
+            while let Some((_, s)) = samples.next_if(|(s, _)| *s < sec) {
+                active = s.active_sessions;
+            }
+            match samples.next_if(|(s, _)| *s == sec) {
+                Some((_, s)) => {
+                    active = s.active_sessions;
+                    series.push(*s);
+                }
+                None => series.push(Sample {
+                    active_sessions: active,
+                    ..Sample::default()
+                }),
+            }
+        }
+        series
+    }
+}
+
+/// collects per second counters of the traffic of the whole network and of each node
+#[derive(Debug)]
+pub struct Throughput {
+    origin: Instant,
+    network: History,
+    nodes: HashMap<NodeId, History>,
+}
+
+impl Default for Throughput {
+    fn default() -> Self {
+        Throughput::new(Instant::now())
+    }
+}
+
+impl Throughput {
+    pub fn new(origin: Instant) -> Self {
+        Throughput {
+            origin,
+            network: History::default(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    fn sec(&self, at: Instant) -> u64 {
+        at.saturating_duration_since(self.origin).as_secs()
+    }
+
+    /// applies `f` to the sample of the current second of both the network and the node
+    fn update(&mut self, node: NodeId, at: Instant, f: impl Fn(&mut Sample, &mut u64)) {
+        let sec = self.sec(at);
+        for history in [&mut self.network, self.nodes.entry(node).or_default()] {
+            let mut active = history.active_sessions;
+            f(history.at(sec), &mut active);
+            history.active_sessions = active;
+            history.at(sec).active_sessions = active;
+        }
+    }
+
+    /// records a packet sent by the node
+    pub fn record_packet(&mut self, node: NodeId, pack_type: &PacketType, at: Instant) {
+        self.update(node, at, |s, _| match pack_type {
+            PacketType::MsgFragment(_) => s.fragments += 1,
+            PacketType::Ack(_) => s.acks += 1,
+            PacketType::Nack(nack) => {
+                let i = match nack.nack_type {
+                    NackType::ErrorInRouting(_) => 0,
+                    NackType::DestinationIsDrone => 1,
+                    NackType::Dropped => 2,
+                    NackType::UnexpectedRecipient(_) => 3,
+                };
+                s.nacks[i] += 1;
+            }
+            PacketType::FloodRequest(_) => s.flood_requests += 1,
+            PacketType::FloodResponse(_) => {}
+        });
+    }
+
+    pub fn record_message_started(&mut self, node: NodeId, at: Instant) {
+        self.update(node, at, |_, active| *active += 1);
+    }
+
+    pub fn record_message_completed(&mut self, node: NodeId, at: Instant) {
+        self.update(node, at, |s, active| {
+            s.messages_completed += 1;
+            *active = active.saturating_sub(1);
+        });
+    }
+
+    /// samples of the last `len` seconds, oldest first, of the given node or of the whole
+    /// network, the seconds without traffic are empty samples
+    pub fn series(&self, node: Option<NodeId>, now: Instant, len: u64) -> Vec<Sample> {
+        let now = self.sec(now);
+        let history = match node {
+            Some(id) => self.nodes.get(&id),
+            None => Some(&self.network),
+        };
+        match history {
+            Some(h) => h.series(now, len),
+            None => vec![Sample::default(); usize::try_from(len).unwrap_or_default()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wg_2024::packet::{Ack, Nack};
+
+    use super::*;
+
+    #[test]
+    fn series() {
+        let start = Instant::now();
+        let mut t = Throughput::new(start);
+        let nack = PacketType::Nack(Nack {
+            fragment_index: 0,
+            nack_type: NackType::Dropped,
+        });
+        t.record_message_started(5, start);
+        t.record_packet(4, &PacketType::Ack(Ack { fragment_index: 0 }), start);
+        t.record_packet(3, &nack, start + Duration::from_secs(1));
+        t.record_packet(4, &nack, start + Duration::from_secs(1));
+        t.record_message_completed(5, start + Duration::from_secs(3));
+
+        let network = t.series(None, start + Duration::from_secs(4), 7);
+        assert_eq!(network.len(), 7);
+        // the first two seconds are before the start
+        assert_eq!(network[..2], [Sample::default(); 2]);
+        assert_eq!(network[2].acks, 1);
+        assert_eq!(network[2].active_sessions, 1);
+        assert_eq!(network[3].nacks, [0, 0, 2, 0]);
+        assert_eq!(network[4].active_sessions, 1);
+        assert_eq!(network[5].messages_completed, 1);
+        assert_eq!(network[6].active_sessions, 0);
+
+        let node = t.series(Some(4), start + Duration::from_secs(4), 7);
+        assert_eq!(node[3].nacks, [0, 0, 1, 0]);
+        assert_eq!(node[2].active_sessions, 0);
+        assert_eq!(t.series(Some(9), start, 3), vec![Sample::default(); 3]);
+    }
+}
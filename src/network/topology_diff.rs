@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use messages::node_event::EventNetworkGraph;
+use wg_2024::{network::NodeId, packet::NodeType};
+
+use super::graph::edge_key;
+use super::node_kind::NodeKind;
+use super::Network;
+
+/// checks that the graph refers to nodes by id: no node appears twice and every neighbor is one
+/// of the nodes of the graph
+pub fn valid_known_network_graph(g: &EventNetworkGraph) -> bool {
+    let mut ids = BTreeSet::new();
+    if !g.nodes.iter().all(|n| ids.insert(n.node_id)) {
+        return false;
+    }
+    g.nodes
+        .iter()
+        .all(|n| n.neighbors.iter().all(|a| ids.contains(a)))
+}
+
+fn node_type(kind: NodeKind) -> NodeType {
+    match kind {
+        NodeKind::Drone { .. } => NodeType::Drone,
+        NodeKind::Client => NodeType::Client,
+        NodeKind::Server => NodeType::Server,
+    }
+}
+
+/// differences between the topology known by a client or server and the real one, edges have
+/// the smaller id first
+#[derive(Debug, Default, PartialEq)]
+pub struct TopologyDiff {
+    /// alive nodes of the network that are not known
+    pub missing_nodes: BTreeSet<NodeId>,
+    /// known nodes that are not in the network
+    pub unknown_nodes: BTreeSet<NodeId>,
+    /// known nodes that crashed or failed
+    pub crashed_nodes: BTreeSet<NodeId>,
+    /// known nodes with a different type, with the known type first
+    pub wrong_types: BTreeMap<NodeId, (NodeType, NodeType)>,
+    /// edges that are both known and in the network
+    pub correct_edges: BTreeSet<(NodeId, NodeId)>,
+    /// edges of the network that are not known
+    pub missing_edges: BTreeSet<(NodeId, NodeId)>,
+    /// known edges that are not in the network
+    pub stale_edges: BTreeSet<(NodeId, NodeId)>,
+}
+
+impl TopologyDiff {
+    /// compares the known graph with the network, nodes and edges are matched by id
+    pub fn new(network: &Network, known: &EventNetworkGraph) -> Self {
+        let mut diff = TopologyDiff::default();
+        let known_ids: BTreeSet<NodeId> = known.nodes.iter().map(|n| n.node_id).collect();
+
+        for n in network.nodes() {
+            let down =
+                n.failure.is_some() || matches!(n.kind, NodeKind::Drone { crashed: true, .. });
+            if !down && !known_ids.contains(&n.id) {
+                diff.missing_nodes.insert(n.id);
+            }
+        }
+        for k in &known.nodes {
+            let Some(n) = network.get_node_from_id(k.node_id) else {
+                diff.unknown_nodes.insert(k.node_id);
+                continue;
+            };
+            if n.failure.is_some() || matches!(n.kind, NodeKind::Drone { crashed: true, .. }) {
+                diff.crashed_nodes.insert(n.id);
+            }
+            let actual = node_type(n.kind);
+            if k.node_type != actual {
+                diff.wrong_types.insert(n.id, (k.node_type, actual));
+            }
+        }
+
+        let known_edges: BTreeSet<(NodeId, NodeId)> = known
+            .nodes
+            .iter()
+            .flat_map(|n| n.neighbors.iter().map(|a| edge_key(n.node_id, *a)))
+            .collect();
+        let real_edges: BTreeSet<(NodeId, NodeId)> =
+            network.graph().edges().map(|(key, _)| key).collect();
+        diff.correct_edges = known_edges.intersection(&real_edges).copied().collect();
+        diff.missing_edges = real_edges.difference(&known_edges).copied().collect();
+        diff.stale_edges = known_edges.difference(&real_edges).copied().collect();
+        diff
+    }
+
+    /// whether the known topology matches the network
+    pub fn is_accurate(&self) -> bool {
+        self.missing_nodes.is_empty()
+            && self.unknown_nodes.is_empty()
+            && self.crashed_nodes.is_empty()
+            && self.wrong_types.is_empty()
+            && self.missing_edges.is_empty()
+            && self.stale_edges.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use messages::node_event::EventNetworkNode;
+
+    use super::*;
+    use crate::network::tests::line_network;
+
+    fn known(nodes: &[(NodeId, NodeType, &[NodeId])]) -> EventNetworkGraph {
+        EventNetworkGraph {
+            nodes: nodes
+                .iter()
+                .map(|(node_id, node_type, neighbors)| EventNetworkNode {
+                    node_id: *node_id,
+                    node_type: *node_type,
+                    neighbors: neighbors.to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn validity() {
+        use NodeType::{Client, Drone};
+        assert!(valid_known_network_graph(&known(&[
+            (5, Client, &[40]),
+            (40, Drone, &[5])
+        ])));
+        assert!(!valid_known_network_graph(&known(&[(5, Client, &[1])])));
+        assert!(!valid_known_network_graph(&known(&[
+            (5, Client, &[]),
+            (5, Drone, &[])
+        ])));
+    }
+
+    #[test]
+    fn diff() {
+        use NodeType::{Client, Drone, Server};
+        let mut network = line_network();
+        network.crash_drones(&[1]).unwrap();
+
+        let graph = known(&[
+            (1, Drone, &[2]),
+            (2, Drone, &[1, 3]),
+            (3, Drone, &[2, 4]),
+            (4, Server, &[3, 5]),
+            (5, Client, &[4]),
+            (9, Drone, &[]),
+        ]);
+        let diff = TopologyDiff::new(&network, &graph);
+        assert_eq!(diff.missing_nodes, BTreeSet::from([6]));
+        assert_eq!(diff.unknown_nodes, BTreeSet::from([9]));
+        assert_eq!(diff.crashed_nodes, BTreeSet::from([1]));
+        assert_eq!(diff.wrong_types, BTreeMap::from([(4, (Server, Drone))]));
+        assert_eq!(diff.stale_edges, BTreeSet::from([(1, 2)]));
+        assert_eq!(diff.missing_edges, BTreeSet::from([(3, 6), (4, 6)]));
+        assert_eq!(diff.correct_edges.len(), 3);
+        assert!(!diff.is_accurate());
+    }
+}
@@ -0,0 +1,224 @@
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use wg_2024::network::NodeId;
+
+use crate::network::node_kind::NodeKind;
+
+/// how the pdr of a drone changes over time during a sweep
+#[derive(Debug, Clone, PartialEq)]
+pub enum SweepKind {
+    /// linearly goes from `from` to `to` in the given time, in steps of 0.01
+    Ramp {
+        from: f32,
+        to: f32,
+        duration: Duration,
+    },
+    /// sets each of the values in order, waiting `interval` between each of them
+    Steps {
+        values: Vec<f32>,
+        interval: Duration,
+    },
+}
+
+/// schedule that changes the pdr of a drone over time
+#[derive(Debug, Clone)]
+pub struct PdrSweep {
+    pub drone: NodeId,
+    pub kind: SweepKind,
+    started: Instant,
+    last_sent: Option<f32>,
+    finished: bool,
+}
+
+impl PdrSweep {
+    pub fn new(drone: NodeId, kind: SweepKind, now: Instant) -> Self {
+        PdrSweep {
+            drone,
+            kind,
+            started: now,
+            last_sent: None,
+            finished: false,
+        }
+    }
+
+    /// returns the pdr that should be set at the given instant, if it is different from the last
+    /// one that was returned
+    pub fn due(&mut self, now: Instant) -> Option<f32> {
+        if self.finished {
+            return None;
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        let value = match &self.kind {
+            SweepKind::Ramp { from, to, duration } => {
+                let progress = if duration.is_zero() {
+                    1.0
+                } else {
+                    (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+                };
+                if progress >= 1.0 {
+                    self.finished = true;
+                }
+                // rounded to the same steps used in the ChangePdr window
+                ((from + (to - from) * progress) * 100.0).round() / 100.0
+            }
+            SweepKind::Steps { values, interval } => {
+                let step = if interval.is_zero() {
+                    values.len()
+                } else {
+                    usize::try_from(elapsed.as_nanos() / interval.as_nanos())
+                        .unwrap_or(usize::MAX)
+                };
+                if step + 1 >= values.len() {
+                    self.finished = true;
+                }
+                *values.get(step).or(values.last())?
+            }
+        };
+        if self.last_sent == Some(value) {
+            None
+        } else {
+            self.last_sent = Some(value);
+            Some(value)
+        }
+    }
+
+    /// true once the last value of the sweep has been returned by `due`
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// progress of the sweep between 0 and 1
+    pub fn progress(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.started).as_secs_f64();
+        let total = match &self.kind {
+            SweepKind::Ramp { duration, .. } => duration.as_secs_f64(),
+            SweepKind::Steps { values, interval } => {
+                interval.as_secs_f64() * values.len().saturating_sub(1) as f64
+            }
+        };
+        if total == 0.0 {
+            1.0
+        } else {
+            (elapsed / total).min(1.0)
+        }
+    }
+}
+
+impl std::fmt::Display for PdrSweep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            SweepKind::Ramp { from, to, duration } => write!(
+                f,
+                "ramp {from:.2}→{to:.2} over {:.1}s",
+                duration.as_secs_f32()
+            ),
+            SweepKind::Steps { values, interval } => {
+                write!(f, "steps {values:?} every {:.1}s", interval.as_secs_f32())
+            }
+        }
+    }
+}
+
+impl crate::MySimulationController {
+    /// starts a sweep on the given drone, replacing the one it had
+    pub(crate) fn start_pdr_sweep(&mut self, drone: NodeId, kind: SweepKind) {
+        info!("starting pdr sweep on drone #{drone}: {kind:?}");
+        self.pdr_sweeps.retain(|s| s.drone != drone);
+        self.pdr_sweeps
+            .push(PdrSweep::new(drone, kind, Instant::now()));
+    }
+
+    /// stops the sweep of the given drone, returns false if it had none
+    pub(crate) fn stop_pdr_sweep(&mut self, drone: NodeId) -> bool {
+        let before = self.pdr_sweeps.len();
+        self.pdr_sweeps.retain(|s| s.drone != drone);
+        before != self.pdr_sweeps.len()
+    }
+
+    /// applies the pdr changes of the sweeps that are due, sweeps that are finished or whose
+    /// drone crashed are removed
+    pub(crate) fn run_pdr_sweeps(&mut self) {
+        let now = Instant::now();
+        let mut sweeps = std::mem::take(&mut self.pdr_sweeps);
+        sweeps.retain_mut(|sweep| {
+            let alive = matches!(
+                self.network.get_node_from_id(sweep.drone).map(|n| n.kind),
+                Some(NodeKind::Drone { crashed: false, .. })
+            );
+            if !alive {
+                warn!("stopping pdr sweep of #{}, drone is not alive", sweep.drone);
+                return false;
+            }
+            if let Some(pdr) = sweep.due(now) {
+                info!("pdr sweep setting pdr of #{} to {pdr}", sweep.drone);
+                self.change_pdr(sweep.drone, pdr);
+            }
+            !sweep.is_finished()
+        });
+        // sweeps started while running the others are kept too
+        sweeps.append(&mut self.pdr_sweeps);
+        self.pdr_sweeps = sweeps;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp() {
+        let start = Instant::now();
+        let mut sweep = PdrSweep::new(
+            1,
+            SweepKind::Ramp {
+                from: 0.1,
+                to: 0.5,
+                duration: Duration::from_secs(4),
+            },
+            start,
+        );
+        assert_eq!(sweep.due(start), Some(0.1));
+        assert_eq!(sweep.due(start), None);
+        assert_eq!(sweep.due(start + Duration::from_secs(2)), Some(0.3));
+        assert!(!sweep.is_finished());
+        assert_eq!(sweep.due(start + Duration::from_secs(5)), Some(0.5));
+        assert!(sweep.is_finished());
+        assert_eq!(sweep.due(start + Duration::from_secs(6)), None);
+    }
+
+    #[test]
+    fn steps() {
+        let start = Instant::now();
+        let mut sweep = PdrSweep::new(
+            1,
+            SweepKind::Steps {
+                values: vec![0.2, 0.4, 0.8],
+                interval: Duration::from_secs(1),
+            },
+            start,
+        );
+        assert_eq!(sweep.due(start), Some(0.2));
+        assert_eq!(sweep.due(start + Duration::from_millis(500)), None);
+        assert_eq!(sweep.due(start + Duration::from_millis(1500)), Some(0.4));
+        assert!(!sweep.is_finished());
+        assert_eq!(sweep.due(start + Duration::from_secs(10)), Some(0.8));
+        assert!(sweep.is_finished());
+    }
+
+    #[test]
+    fn sub_millisecond_steps() {
+        let start = Instant::now();
+        let mut sweep = PdrSweep::new(
+            1,
+            SweepKind::Steps {
+                values: vec![0.1, 0.2],
+                interval: Duration::from_micros(100),
+            },
+            start,
+        );
+        assert_eq!(sweep.due(start), Some(0.1));
+        assert_eq!(sweep.due(start + Duration::from_micros(150)), Some(0.2));
+        assert!(sweep.is_finished());
+    }
+}
@@ -0,0 +1,261 @@
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "appmessage_through_crossbeam")]
+use crossbeam_channel::Select;
+use crossbeam_channel::TryRecvError;
+use log::debug;
+use wg_2024::controller::DroneEvent;
+
+use crate::keypress_handler;
+
+/// shortest time between two frames, so that at most 30 frames per second are drawn
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+/// the interface is redrawn at least this often even if nothing changed, e.g. to fade the edges
+/// that were active or to follow a resize of the terminal
+pub const IDLE_FRAME_INTERVAL: Duration = Duration::from_millis(250);
+/// longest wait for keys or events when there is nothing to do
+const IDLE_WAIT: Duration = Duration::from_millis(10);
+/// events ingested between two checks of the deadline
+const EVENT_BATCH: usize = 256;
+
+/// decides when the interface is drawn: as soon as the frame interval passed if the state
+/// changed, otherwise only once the idle interval passed
+#[derive(Debug)]
+pub(crate) struct FrameClock {
+    dirty: bool,
+    last_frame: Option<Instant>,
+}
+
+impl FrameClock {
+    pub(crate) fn new() -> Self {
+        FrameClock {
+            dirty: true,
+            last_frame: None,
+        }
+    }
+
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// time left before the next frame should be drawn
+    pub(crate) fn until_next_frame(&self, now: Instant) -> Duration {
+        let Some(last) = self.last_frame else {
+            return Duration::ZERO;
+        };
+        let interval = if self.dirty {
+            FRAME_INTERVAL
+        } else {
+            IDLE_FRAME_INTERVAL
+        };
+        interval.saturating_sub(now.saturating_duration_since(last))
+    }
+
+    pub(crate) fn should_draw(&self, now: Instant) -> bool {
+        self.until_next_frame(now).is_zero()
+    }
+
+    pub(crate) fn drawn(&mut self, now: Instant) {
+        self.dirty = false;
+        self.last_frame = Some(now);
+    }
+}
+
+/// rates shown in the footer, computed over windows of one second
+#[derive(Debug)]
+pub struct Hud {
+    pub events_per_sec: f64,
+    pub frames_per_sec: f64,
+    /// events waiting in the drone and node event channels
+    pub backlog: usize,
+    window_start: Instant,
+    window_events: u64,
+    window_frames: u64,
+}
+
+impl Hud {
+    pub(crate) fn new(now: Instant) -> Self {
+        Hud {
+            events_per_sec: 0.0,
+            frames_per_sec: 0.0,
+            backlog: 0,
+            window_start: now,
+            window_events: 0,
+            window_frames: 0,
+        }
+    }
+
+    pub(crate) fn record_events(&mut self, events: u64) {
+        self.window_events += events;
+    }
+
+    pub(crate) fn record_frame(&mut self) {
+        self.window_frames += 1;
+    }
+
+    /// updates the backlog, and the rates once a second passed since the last time
+    pub(crate) fn update(&mut self, now: Instant, backlog: usize) {
+        self.backlog = backlog;
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < Duration::from_secs(1) {
+            return;
+        }
+        self.events_per_sec = self.window_events as f64 / elapsed.as_secs_f64();
+        self.frames_per_sec = self.window_frames as f64 / elapsed.as_secs_f64();
+        self.window_start = now;
+        self.window_events = 0;
+        self.window_frames = 0;
+    }
+}
+
+impl crate::MySimulationController {
+    /// events waiting to be ingested
+    pub(crate) fn backlog(&self) -> usize {
+        self.droneevent_recv.len() + self.nodeevent_recv.len()
+    }
+
+    /// saves the drone and node events that are waiting, taking turns between the two channels,
+    /// until both are empty or `deadline` passes, returns how many were saved
+    /// # Panics
+    /// panics when a channel is disconnected
+    pub(crate) fn ingest_events(&mut self, deadline: Instant) -> u64 {
+        let mut ingested = 0;
+        loop {
+            for _ in 0..EVENT_BATCH {
+                let before = ingested;
+                match self.droneevent_recv.try_recv() {
+                    Ok(event) => {
+                        if let DroneEvent::ControllerShortcut(ref packet) = event {
+                            self.shortcut_packet(packet.clone());
+                        }
+                        self.save_droneevent(event);
+                        ingested += 1;
+                    }
+                    Err(TryRecvError::Empty) => {}
+                    Err(err) => panic!("error for droneevent receiver: {err:?}"),
+                }
+                match self.nodeevent_recv.try_recv() {
+                    Ok(event) => {
+                        self.save_nodeevent(event);
+                        ingested += 1;
+                    }
+                    Err(TryRecvError::Empty) => {}
+                    Err(err) => panic!("error for nodevent receiver: {err:?}"),
+                }
+                if ingested == before {
+                    self.events_ingested += ingested;
+                    return ingested;
+                }
+            }
+            if Instant::now() >= deadline {
+                self.events_ingested += ingested;
+                return ingested;
+            }
+        }
+    }
+
+    /// saves every event that is waiting, used by the benchmarks to measure the ingestion alone
+    #[cfg(feature = "benchmarks")]
+    pub fn ingest_pending(&mut self) -> u64 {
+        self.ingest_events(Instant::now() + Duration::from_secs(3600))
+    }
+
+    /// waits at most `timeout` for a key or an event, then handles all the keys that were
+    /// pressed, returns whether any was handled
+    pub(crate) fn handle_input(&mut self, timeout: Duration) -> bool {
+        let timeout = if self.backlog() > 0 {
+            Duration::ZERO
+        } else {
+            timeout.min(IDLE_WAIT)
+        };
+        let mut handled = false;
+
+        #[cfg(feature = "appmessage_through_crossbeam")]
+        {
+            let Some(rcv) = self.keyevent_recv.clone() else {
+                return false;
+            };
+            if rcv.is_empty() && !timeout.is_zero() {
+                let mut sel = Select::new();
+                sel.recv(&rcv);
+                sel.recv(&self.droneevent_recv);
+                sel.recv(&self.nodeevent_recv);
+                let _ = sel.ready_timeout(timeout);
+            }
+            while self.running {
+                let Some(message) = keypress_handler::handle_keypress_from_recv(&self.screen, &rcv)
+                else {
+                    break;
+                };
+                debug!(
+                    "received AppMessage through crossbeam channel: {:?}",
+                    message
+                );
+                self.transition(&message);
+                handled = true;
+            }
+        }
+
+        #[cfg(not(feature = "appmessage_through_crossbeam"))]
+        #[cfg_attr(coverage_nightly, coverage(off))]
+        {
+            let mut timeout = timeout;
+            while self.running {
+                let Some(message) =
+                    keypress_handler::handle_crossterm_events(&self.screen, timeout)
+                else {
+                    break;
+                };
+                debug!("received AppMessage: {:?}", message);
+                self.transition(&message);
+                handled = true;
+                timeout = Duration::ZERO;
+            }
+        }
+
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_clock() {
+        let start = Instant::now();
+        let mut clock = FrameClock::new();
+        assert!(clock.should_draw(start));
+        clock.drawn(start);
+
+        // nothing changed, only the idle refresh is drawn
+        assert!(!clock.should_draw(start + FRAME_INTERVAL));
+        assert!(clock.should_draw(start + IDLE_FRAME_INTERVAL));
+
+        // changes are drawn at most once per frame interval
+        clock.mark_dirty();
+        assert!(!clock.should_draw(start + FRAME_INTERVAL / 2));
+        assert_eq!(
+            clock.until_next_frame(start + FRAME_INTERVAL / 2),
+            FRAME_INTERVAL - FRAME_INTERVAL / 2
+        );
+        assert!(clock.should_draw(start + FRAME_INTERVAL));
+    }
+
+    #[test]
+    fn hud_rates() {
+        let start = Instant::now();
+        let mut hud = Hud::new(start);
+        hud.record_events(5000);
+        hud.record_frame();
+        hud.update(start + Duration::from_millis(500), 7);
+        assert_eq!(hud.backlog, 7);
+        assert_eq!(hud.events_per_sec, 0.0);
+
+        hud.record_events(5000);
+        hud.record_frame();
+        hud.update(start + Duration::from_secs(2), 0);
+        assert_eq!(hud.events_per_sec, 5000.0);
+        assert_eq!(hud.frames_per_sec, 1.0);
+    }
+}
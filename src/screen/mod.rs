@@ -1,16 +1,69 @@
+use std::collections::BTreeSet;
+
 use wg_2024::network::NodeId;
 
+use crate::network::nacks::NackGrouping;
 use crate::network::node_kind::NodeKind;
 
 #[derive(Debug)]
 /// contains all the different gui states, meaning, different windows or popups
 pub enum Window {
-    AddConnection { origin: NodeId },
-    ChangePdr { pdr: f32 },
-    Detail { tab: usize },
-    Error { message: &'static str },
+    AddConnection {
+        origin: NodeId,
+    },
+    /// `batch` is set when the pdr is changed for all the marked drones instead of the focused one,
+    /// `input` is the value typed by the user, which takes the place of `pdr` when not empty
+    ChangePdr {
+        pdr: f32,
+        batch: bool,
+        input: String,
+        error: Option<String>,
+    },
+    /// command prompt, `history` is the position in the command history when browsing it
+    Command {
+        input: String,
+        history: Option<usize>,
+        error: Option<String>,
+        candidates: Vec<String>,
+    },
+    Detail {
+        tab: usize,
+    },
+    /// incidents caused by malformed events
+    Diagnostics,
+    Error {
+        message: &'static str,
+    },
+    /// propagation trees of the floods
+    Floods,
     Main,
+    /// charts of the traffic over time, of the whole network or, when `per_node` is set, of the
+    /// focused node
+    Metrics {
+        per_node: bool,
+    },
     Move,
+    /// nacks counted by type, grouped by the node that created them or by session
+    Nacks {
+        grouping: NackGrouping,
+    },
+    /// conformance to the protocol of each drone implementation
+    ReportCard,
+    /// source route of the packet selected in the table `tab` of the detail window, `step` is the
+    /// hop reached when stepping through the route
+    Route {
+        tab: usize,
+        hops: Vec<NodeId>,
+        hop_index: usize,
+        step: usize,
+    },
+}
+
+impl Window {
+    /// whether characters that are not bound to any action are typed into the window
+    pub fn accepts_text(&self) -> bool {
+        matches!(self, Window::Command { .. } | Window::ChangePdr { .. })
+    }
 }
 
 /// contains all information about the state of the gui, and the currently selected node
@@ -18,4 +71,8 @@ pub struct Screen {
     pub focus: NodeId,
     pub kind: NodeKind,
     pub window: Window,
+    /// whether the help overlay is shown on top of the current window
+    pub help: bool,
+    /// nodes marked for batch operations
+    pub marked: BTreeSet<NodeId>,
 }
@@ -0,0 +1,181 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use log::{debug, info, warn};
+use serde::Serialize;
+use wg_2024::{network::NodeId, packet::Packet};
+
+/// what the sc does with the packets that drones send through the controller shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ShortcutPolicy {
+    /// sends the packet straight to its destination
+    #[default]
+    Immediate,
+    /// sends the packet straight to its destination after the given time
+    Delayed(Duration),
+    /// drops the packet
+    Drop,
+    /// sends the packet to the next hop of a valid path from the drone to the destination
+    Reroute,
+}
+
+impl ShortcutPolicy {
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShortcutPolicy::Immediate => "immediate",
+            ShortcutPolicy::Delayed(_) => "delayed",
+            ShortcutPolicy::Drop => "drop",
+            ShortcutPolicy::Reroute => "reroute",
+        }
+    }
+}
+
+impl Display for ShortcutPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShortcutPolicy::Delayed(delay) => write!(f, "delayed by {:.1}s", delay.as_secs_f32()),
+            policy => write!(f, "{}", policy.name()),
+        }
+    }
+}
+
+/// what happened to the shortcutted packets while a policy was in use
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct PolicyStats {
+    /// sent to their destination
+    pub delivered: u64,
+    /// waited before being sent, they are also counted as delivered or failed once sent
+    pub delayed: u64,
+    pub dropped: u64,
+    /// sent along a new path
+    pub rerouted: u64,
+    /// could not be sent, e.g. because the destination crashed or there was no path
+    pub failed: u64,
+}
+
+/// a packet waiting to be sent by the delayed policy
+#[derive(Debug)]
+pub(crate) struct DelayedShortcut {
+    pub due: Instant,
+    pub packet: Packet,
+}
+
+impl crate::MySimulationController {
+    /// chooses what happens to the packets shortcutted from now on
+    pub fn set_shortcut_policy(&mut self, policy: ShortcutPolicy) {
+        info!("shortcut policy set to {policy}");
+        self.shortcut_policy = policy;
+    }
+
+    /// counts of what happened to the shortcutted packets, for each policy that was used
+    #[must_use]
+    pub fn shortcut_stats(&self) -> &BTreeMap<&'static str, PolicyStats> {
+        &self.shortcut_stats
+    }
+
+    fn policy_stats(&mut self, policy: ShortcutPolicy) -> &mut PolicyStats {
+        self.shortcut_stats.entry(policy.name()).or_default()
+    }
+
+    /// handles a packet shortcutted by a drone with the current policy, a packet with no
+    /// destination or with a destination that is not in the network is recorded as an incident
+    pub(crate) fn shortcut_packet(&mut self, packet: Packet) {
+        // the drone that sent the packet through the sc
+        let source = packet.routing_header.previous_hop();
+        let Some(dst) = packet.routing_header.destination() else {
+            self.incident(
+                source,
+                format!("Destination for packet {packet} not found"),
+                &packet,
+            );
+            return;
+        };
+        if self.network.get_node_from_id(dst).is_none() {
+            self.incident(
+                source,
+                format!("destination #{dst} of shortcutted packet is not in the network"),
+                &packet,
+            );
+            return;
+        }
+
+        let policy = self.shortcut_policy;
+        match policy {
+            ShortcutPolicy::Immediate => self.deliver_shortcut(policy, packet),
+            ShortcutPolicy::Delayed(delay) => {
+                self.policy_stats(policy).delayed += 1;
+                self.delayed_shortcuts.push_back(DelayedShortcut {
+                    due: Instant::now() + delay,
+                    packet,
+                });
+            }
+            ShortcutPolicy::Drop => {
+                debug!("Dropped shortcutted packet: {packet}");
+                self.policy_stats(policy).dropped += 1;
+            }
+            ShortcutPolicy::Reroute => self.reroute_shortcut(source, dst, packet),
+        }
+    }
+
+    /// sends the delayed packets whose time has come
+    pub(crate) fn run_delayed_shortcuts(&mut self) {
+        let now = Instant::now();
+        while self.delayed_shortcuts.front().is_some_and(|d| d.due <= now) {
+            let delayed = self
+                .delayed_shortcuts
+                .pop_front()
+                .expect("front was checked");
+            self.deliver_shortcut(ShortcutPolicy::Delayed(Duration::ZERO), delayed.packet);
+        }
+    }
+
+    /// sends the packet directly to its final destination
+    fn deliver_shortcut(&mut self, policy: ShortcutPolicy, mut packet: Packet) {
+        let Some(dst) = packet.routing_header.destination() else {
+            unreachable!("shortcutted packets are checked to have a destination")
+        };
+        packet.routing_header.hop_index = packet.routing_header.hops.len() - 1;
+        self.send_shortcut(policy, dst, packet, false);
+    }
+
+    /// replaces the rest of the route with the shortest path from the drone that shortcutted the
+    /// packet to its destination, then sends it to the next hop
+    fn reroute_shortcut(&mut self, source: Option<NodeId>, dst: NodeId, mut packet: Packet) {
+        let policy = ShortcutPolicy::Reroute;
+        let header = &mut packet.routing_header;
+        let path = source
+            .and_then(|s| self.network.route(s, dst))
+            .filter(|path| path.len() > 1);
+        let Some(path) = path else {
+            warn!("no path to reroute shortcutted packet {packet}");
+            self.policy_stats(policy).failed += 1;
+            return;
+        };
+        header.hops.truncate(header.hop_index - 1);
+        header.hops.extend(path);
+        let next = header.hops[header.hop_index];
+        self.send_shortcut(policy, next, packet, true);
+    }
+
+    fn send_shortcut(&mut self, policy: ShortcutPolicy, to: NodeId, packet: Packet, reroute: bool) {
+        let sent = self
+            .packet_send
+            .get(&to)
+            .is_some_and(|sender| sender.send(packet.clone()).is_ok());
+        let stats = self.policy_stats(policy);
+        if !sent {
+            warn!("could not send shortcutted packet to #{to}: {packet}");
+            stats.failed += 1;
+        } else if reroute {
+            debug!("Rerouted shortcutted packet: {packet}");
+            stats.rerouted += 1;
+        } else {
+            debug!("Shortcutted packet: {packet}");
+            stats.delivered += 1;
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use log::{error, info, warn};
+use wg_2024::{controller::DroneCommand, network::NodeId};
+
+use crate::diagnostics::panic_message;
+use crate::network::node_kind::NodeKind;
+
+/// how long the node threads are waited for on quit, unless set with `set_shutdown_timeout`
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// how the node threads ended when the sc quit
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShutdownReport {
+    /// threads that exited normally
+    pub exited: Vec<NodeId>,
+    /// threads that panicked, with their panic message
+    pub panicked: Vec<(NodeId, String)>,
+    /// threads still running when the timeout expired, they are left detached
+    pub hanging: Vec<NodeId>,
+}
+
+impl ShutdownReport {
+    pub fn is_clean(&self) -> bool {
+        self.panicked.is_empty() && self.hanging.is_empty()
+    }
+}
+
+impl Display for ShutdownReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} node threads exited", self.exited.len())?;
+        for (id, message) in &self.panicked {
+            write!(f, "\nnode #{id} panicked: {message}")?;
+        }
+        for id in &self.hanging {
+            write!(f, "\nnode #{id} did not terminate")?;
+        }
+        Ok(())
+    }
+}
+
+/// waits for all the threads to finish, but no longer than `timeout`, then joins the finished
+/// ones
+pub fn join_all(handles: HashMap<NodeId, JoinHandle<()>>, timeout: Duration) -> ShutdownReport {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline && handles.values().any(|h| !h.is_finished()) {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut report = ShutdownReport::default();
+    for (id, handle) in handles {
+        if !handle.is_finished() {
+            report.hanging.push(id);
+            continue;
+        }
+        match handle.join() {
+            Ok(()) => report.exited.push(id),
+            Err(e) => report
+                .panicked
+                .push((id, panic_message(e.as_ref()).to_string())),
+        }
+    }
+    report.exited.sort_unstable();
+    report.panicked.sort_unstable_by_key(|(id, _)| *id);
+    report.hanging.sort_unstable();
+    report
+}
+
+impl crate::MySimulationController {
+    /// sets how long the node threads are waited for when the sc quits
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// report of the last shutdown, available once `run` returns
+    #[must_use]
+    pub fn shutdown_report(&self) -> Option<&ShutdownReport> {
+        self.shutdown_report.as_ref()
+    }
+
+    /// crashes every drone that is still alive, drops every sender of the sc, event sender
+    /// included, so that the clients and servers see their channels disconnect too, then joins the
+    /// node threads
+    pub(crate) fn shutdown(&mut self) {
+        info!("shutting down, crashing all the drones...");
+        for node in self.network.nodes() {
+            if let NodeKind::Drone { crashed: false, .. } = node.kind {
+                if let Some(sender) = self.command_send.get(&node.id) {
+                    let _ = sender.send(DroneCommand::Crash);
+                }
+            }
+        }
+        self.command_send.clear();
+        self.packet_send.clear();
+        self.droneevent_send = None;
+
+        let report = join_all(
+            std::mem::take(&mut self.node_handles),
+            self.shutdown_timeout,
+        );
+        if report.is_clean() {
+            info!("shutdown completed: {report}");
+        } else {
+            for (id, message) in &report.panicked {
+                error!("node #{id} panicked during shutdown: {message}");
+            }
+            for id in &report.hanging {
+                warn!(
+                    "node #{id} did not terminate within {:?}",
+                    self.shutdown_timeout
+                );
+            }
+        }
+        self.shutdown_report = Some(report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let handles = HashMap::from([
+            (1, thread::spawn(|| ())),
+            (2, thread::spawn(|| panic!("stuck in a loop"))),
+            (3, thread::spawn(|| thread::sleep(Duration::from_secs(5)))),
+        ]);
+        let report = join_all(handles, Duration::from_millis(200));
+        assert_eq!(report.exited, vec![1]);
+        assert_eq!(report.panicked, vec![(2, "stuck in a loop".to_string())]);
+        assert_eq!(report.hanging, vec![3]);
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.to_string(),
+            "1 node threads exited\nnode #2 panicked: stuck in a loop\nnode #3 did not terminate"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::info;
+use serde::Serialize;
+use wg_2024::network::NodeId;
+
+use crate::network::node_kind::NodeKind;
+use crate::network::node_stats::{NodeStats, PacketCounts};
+use crate::network::Network;
+use crate::shortcut::PolicyStats;
+
+/// directory used by the on demand export and by the export on quit when none was configured
+pub const DEFAULT_STATS_DIR: &str = "sc_stats";
+
+#[derive(Debug, Serialize)]
+struct NodeRow<'a> {
+    id: NodeId,
+    kind: &'static str,
+    thread_name: &'a str,
+    crashed: bool,
+    configured_pdr: Option<f32>,
+    /// dropped fragments over the fragments that reached the drone, None before the first one
+    observed_pdr: Option<f64>,
+    frags_sent: u64,
+    frags_dropped: u64,
+    #[serde(flatten)]
+    stats: &'a NodeStats,
+}
+
+#[derive(Debug, Serialize)]
+struct EdgeRow {
+    from: NodeId,
+    to: NodeId,
+    /// false when the edge was removed, e.g. because one of its ends crashed
+    active: bool,
+    packets: PacketCounts,
+}
+
+#[derive(Debug, Serialize)]
+struct Snapshot<'a> {
+    nodes: Vec<NodeRow<'a>>,
+    edges: Vec<EdgeRow>,
+}
+
+fn snapshot(network: &Network) -> Snapshot<'_> {
+    let mut nodes: Vec<NodeRow> = network
+        .nodes()
+        .iter()
+        .map(|n| {
+            let (kind, crashed, configured_pdr) = match n.kind {
+                NodeKind::Drone { pdr, crashed } => ("drone", crashed, Some(pdr)),
+                NodeKind::Client => ("client", false, None),
+                NodeKind::Server => ("server", false, None),
+            };
+            NodeRow {
+                id: n.id,
+                kind,
+                thread_name: &n.thread_name,
+                crashed,
+                configured_pdr,
+                observed_pdr: (n.n_frags_sent > 0)
+                    .then(|| n.n_frags_dropped as f64 / n.n_frags_sent as f64),
+                frags_sent: n.n_frags_sent,
+                frags_dropped: n.n_frags_dropped,
+                stats: &n.stats,
+            }
+        })
+        .collect();
+    nodes.sort_by_key(|n| n.id);
+
+    // edges are sorted by the set, removed edges are still exported if packets went through them
+    let edges: Vec<EdgeRow> = network
+        .graph()
+        .edges()
+        .map(|(key, _)| key)
+        .chain(network.edge_stats.keys().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|(from, to)| EdgeRow {
+            from,
+            to,
+            active: network.graph().has_edge(from, to),
+            packets: network
+                .edge_stats
+                .get(&(from, to))
+                .copied()
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    Snapshot { nodes, edges }
+}
+
+fn packet_columns(prefix: &str) -> String {
+    ["fragment", "ack", "nack", "flood_request", "flood_response"]
+        .iter()
+        .map(|t| format!("{prefix}_{t}"))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn packet_values(c: &PacketCounts) -> String {
+    format!(
+        "{},{},{},{},{}",
+        c.fragment, c.ack, c.nack, c.flood_request, c.flood_response
+    )
+}
+
+fn optional<T: ToString>(x: Option<T>) -> String {
+    x.map(|x| x.to_string()).unwrap_or_default()
+}
+
+fn nodes_csv(snapshot: &Snapshot) -> String {
+    let mut csv = format!(
+        "id,kind,thread_name,crashed,configured_pdr,observed_pdr,frags_sent,frags_dropped,{},{},{},messages_started,messages_completed,messages_received\n",
+        packet_columns("sent"),
+        packet_columns("dropped"),
+        packet_columns("shortcutted"),
+    );
+    for n in &snapshot.nodes {
+        let _ = writeln!(
+            csv,
+            "{},{},\"{}\",{},{},{},{},{},{},{},{},{},{},{}",
+            n.id,
+            n.kind,
+            n.thread_name.replace('"', "\"\""),
+            n.crashed,
+            optional(n.configured_pdr),
+            optional(n.observed_pdr),
+            n.frags_sent,
+            n.frags_dropped,
+            packet_values(&n.stats.sent),
+            packet_values(&n.stats.dropped),
+            packet_values(&n.stats.shortcutted),
+            n.stats.messages_started.values().sum::<u64>(),
+            n.stats.messages_completed.values().sum::<u64>(),
+            n.stats.messages_received.values().sum::<u64>(),
+        );
+    }
+    csv
+}
+
+fn edges_csv(snapshot: &Snapshot) -> String {
+    let mut csv = format!("from,to,active,{}\n", packet_columns("packets"));
+    for e in &snapshot.edges {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{}",
+            e.from,
+            e.to,
+            e.active,
+            packet_values(&e.packets)
+        );
+    }
+    csv
+}
+
+/// message counts in long format, one row for each node, event and kind of message
+fn messages_csv(snapshot: &Snapshot) -> String {
+    let mut csv = "id,event,kind,count\n".to_string();
+    for n in &snapshot.nodes {
+        for (event, counts) in [
+            ("started", &n.stats.messages_started),
+            ("completed", &n.stats.messages_completed),
+            ("received", &n.stats.messages_received),
+        ] {
+            for (kind, count) in counts {
+                let _ = writeln!(csv, "{},{event},{kind},{count}", n.id);
+            }
+        }
+    }
+    csv
+}
+
+/// one row for each shortcut policy that was used
+fn shortcuts_csv(stats: &BTreeMap<&'static str, PolicyStats>) -> String {
+    let mut csv = "policy,delivered,delayed,dropped,rerouted,failed\n".to_string();
+    for (policy, s) in stats {
+        let _ = writeln!(
+            csv,
+            "{policy},{},{},{},{},{}",
+            s.delivered, s.delayed, s.dropped, s.rerouted, s.failed
+        );
+    }
+    csv
+}
+
+impl crate::MySimulationController {
+    /// sets the directory where statistics are exported on quit and by default on demand, instead
+    /// of `DEFAULT_STATS_DIR`
+    pub fn set_stats_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.stats_dir = Some(dir.into());
+    }
+
+    /// writes nodes.csv, edges.csv, messages.csv, shortcuts.csv and stats.json inside the given
+    /// directory, creating it if needed
+    pub(crate) fn export_stats(&self, dir: &Path) -> Result<(), String> {
+        let snapshot = snapshot(&self.network);
+        let json = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| format!("could not serialize statistics: {e}"))?;
+        fs::create_dir_all(dir).map_err(|e| format!("could not create {}: {e}", dir.display()))?;
+        for (name, content) in [
+            ("nodes.csv", nodes_csv(&snapshot)),
+            ("edges.csv", edges_csv(&snapshot)),
+            ("messages.csv", messages_csv(&snapshot)),
+            ("shortcuts.csv", shortcuts_csv(&self.shortcut_stats)),
+            ("stats.json", json),
+        ] {
+            let path = dir.join(name);
+            fs::write(&path, content)
+                .map_err(|e| format!("could not write {}: {e}", path.display()))?;
+        }
+        info!("exported statistics to {}", dir.display());
+        Ok(())
+    }
+}
@@ -1,12 +1,16 @@
+use crate::command::{self, DEFAULT_SPAWN_PDR};
 use crate::network;
 use crate::screen;
+use crate::stats_export::DEFAULT_STATS_DIR;
 use crate::utilities;
 use crate::MySimulationController;
 
-use log::{debug, info, trace};
+use log::{debug, error, info, trace};
+use network::nacks::NackGrouping;
 use network::node_kind::NodeKind;
 use screen::Window;
 use utilities::app_message::AppMessage;
+use wg_2024::network::NodeId;
 
 impl MySimulationController {
     #[allow(clippy::too_many_lines)]
@@ -18,6 +22,10 @@ impl MySimulationController {
                 info!("received AppMessage::Quit, exiting...");
                 self.running = false;
             }
+            AppMessage::ToggleHelp => {
+                self.screen.help = !self.screen.help;
+                trace!("help overlay shown: {}", self.screen.help);
+            }
             AppMessage::Crash => match self.screen.window {
                 Window::Detail { tab: _ } if matches!(kind, NodeKind::Drone { .. }) => {
                     match self.crash(id) {
@@ -32,9 +40,71 @@ impl MySimulationController {
                 }
                 _ => {}
             },
+            AppMessage::Revive => match self.screen.window {
+                Window::Detail { tab: _ } if matches!(kind, NodeKind::Drone { .. }) => {
+                    match self.revive_drone(id, None) {
+                        Ok(()) => {
+                            self.screen.window = Window::Main;
+                        }
+                        Err(message) => {
+                            debug!("error reviving drone, switching to Window::Error");
+                            self.screen.window = Window::Error { message };
+                        }
+                    };
+                }
+                _ => {}
+            },
+            AppMessage::OlderPage | AppMessage::NewerPage => {
+                if let Window::Detail { tab } = self.screen.window {
+                    self.change_history_page(tab, matches!(message, AppMessage::OlderPage));
+                }
+            }
+            AppMessage::WindowRoute => {
+                if let Window::Detail { tab } = self.screen.window {
+                    let selected = self.packet_table_state.selected().unwrap_or(usize::MAX);
+                    let packet = match &self.history_page {
+                        Some(page) => page.selected_packet(&self.network, selected),
+                        None => self.network.selected_packet(id, tab, selected),
+                    };
+                    match packet {
+                        Some(packet) => {
+                            let header = &packet.routing_header;
+                            self.screen.window = Window::Route {
+                                tab,
+                                hops: header.hops.clone(),
+                                hop_index: header.hop_index,
+                                step: header.hop_index.min(header.hops.len().saturating_sub(1)),
+                            };
+                        }
+                        None => {
+                            self.screen.window = Window::Error {
+                                message: "the selected row has no packet to follow",
+                            };
+                        }
+                    }
+                }
+            }
+            AppMessage::PreviousHop => {
+                if let Window::Route { ref mut step, .. } = self.screen.window {
+                    *step = step.saturating_sub(1);
+                }
+            }
+            AppMessage::NextHop => {
+                if let Window::Route {
+                    ref mut step,
+                    ref hops,
+                    ..
+                } = self.screen.window
+                {
+                    if *step + 1 < hops.len() {
+                        *step += 1;
+                    }
+                }
+            }
             // for Detail
             AppMessage::ChangeTab => {
                 if let Window::Detail { ref mut tab } = self.screen.window {
+                    self.history_page = None;
                     *tab = tab.saturating_add(1);
                     self.packet_table_state.select(Some(0));
                     *tab %= 3;
@@ -48,11 +118,168 @@ impl MySimulationController {
                     //    *tab %= 3;
                     //}
                 }
+                if let Window::Metrics { ref mut per_node } = self.screen.window {
+                    *per_node = !*per_node;
+                    trace!("On window Metrics, showing single node: {per_node}");
+                }
+                if let Window::Nacks { ref mut grouping } = self.screen.window {
+                    *grouping = match grouping {
+                        NackGrouping::Emitter => NackGrouping::Session,
+                        NackGrouping::Session => NackGrouping::Emitter,
+                    };
+                    self.packet_table_state.select_first();
+                    trace!("On window Nacks, grouping by {grouping:?}");
+                }
             }
             // spawn drone
             AppMessage::SpawnDrone => {
                 if let Window::Main = self.screen.window {
-                    self.spawn_drone();
+                    let id = self.random_unique_id();
+                    if let Err(message) = self.spawn_drone(id, DEFAULT_SPAWN_PDR, &[]) {
+                        debug!("error spawning drone, switching to Window::Error");
+                        self.screen.window = Window::Error { message };
+                    }
+                }
+            }
+            AppMessage::ExportStats => {
+                if let Window::Main = self.screen.window {
+                    let dir = self
+                        .stats_dir
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_STATS_DIR.into());
+                    if let Err(e) = self.export_stats(&dir) {
+                        error!("{e}");
+                        self.screen.window = Window::Error {
+                            message: "could not export the statistics, the reason is in the log",
+                        };
+                    }
+                }
+            }
+            // batch operations on marked nodes
+            AppMessage::ToggleMark => {
+                if let Window::Main = self.screen.window {
+                    if !self.screen.marked.remove(&id) {
+                        self.screen.marked.insert(id);
+                    }
+                    trace!("marked nodes: {:?}", self.screen.marked);
+                }
+            }
+            AppMessage::ClearMarks => {
+                if let Window::Main = self.screen.window {
+                    self.screen.marked.clear();
+                }
+            }
+            AppMessage::WindowChangePDRMarked => {
+                if let Window::Main = self.screen.window {
+                    let pdrs: Vec<Option<f32>> = self
+                        .screen
+                        .marked
+                        .iter()
+                        .map(
+                            |id| match self.network.get_node_from_id(*id).map(|n| n.kind) {
+                                Some(NodeKind::Drone {
+                                    pdr,
+                                    crashed: false,
+                                }) => Some(pdr),
+                                _ => None,
+                            },
+                        )
+                        .collect();
+                    self.screen.window = match pdrs.first() {
+                        Some(Some(pdr)) if pdrs.iter().all(Option::is_some) => Window::ChangePdr {
+                            pdr: *pdr,
+                            batch: true,
+                            input: String::new(),
+                            error: None,
+                        },
+                        _ => Window::Error {
+                            message: "the pdr can only be changed for drones that have not crashed",
+                        },
+                    };
+                }
+            }
+            AppMessage::CrashMarked => {
+                if let Window::Main = self.screen.window {
+                    let marked: Vec<NodeId> = self.screen.marked.iter().copied().collect();
+                    match self.crash_drones(&marked) {
+                        Ok(()) => {
+                            info!("crashed marked drones {marked:?}");
+                            self.screen.marked.clear();
+                            self.refresh_focus();
+                        }
+                        Err(message) => {
+                            debug!("error crashing marked drones, switching to Window::Error");
+                            self.screen.window = Window::Error { message };
+                        }
+                    }
+                }
+            }
+            AppMessage::ConnectMarked => {
+                if let Window::Main = self.screen.window {
+                    let targets: Vec<NodeId> = self
+                        .screen
+                        .marked
+                        .iter()
+                        .copied()
+                        .filter(|x| *x != id)
+                        .collect();
+                    if let Err(message) = self.add_connections(id, &targets) {
+                        debug!("error connecting to marked nodes, switching to Window::Error");
+                        self.screen.window = Window::Error { message };
+                    }
+                }
+            }
+            // command prompt
+            AppMessage::InputChar(c) => match self.screen.window {
+                Window::Command {
+                    ref mut input,
+                    ref mut error,
+                    ..
+                } => {
+                    input.push(*c);
+                    *error = None;
+                }
+                // only a number can be typed as pdr
+                Window::ChangePdr {
+                    ref mut input,
+                    ref mut error,
+                    ..
+                } if c.is_ascii_digit() || *c == '.' => {
+                    input.push(*c);
+                    *error = None;
+                }
+                _ => {}
+            },
+            AppMessage::DeleteChar => match self.screen.window {
+                Window::Command {
+                    ref mut input,
+                    ref mut error,
+                    ..
+                }
+                | Window::ChangePdr {
+                    ref mut input,
+                    ref mut error,
+                    ..
+                } => {
+                    input.pop();
+                    *error = None;
+                }
+                _ => {}
+            },
+            AppMessage::Complete => {
+                if let Window::Command {
+                    ref mut input,
+                    ref mut candidates,
+                    ..
+                } = self.screen.window
+                {
+                    let ids: Vec<NodeId> = self.network.nodes().iter().map(|n| n.id).collect();
+                    (*input, *candidates) = command::complete(input, &ids);
+                }
+            }
+            AppMessage::Cancel => {
+                if let Window::Command { .. } = self.screen.window {
+                    self.screen.window = Window::Main;
                 }
             }
             // Window changes
@@ -61,6 +288,16 @@ impl MySimulationController {
                     self.screen.window = Window::AddConnection { origin: id }
                 }
             }
+            AppMessage::WindowCommand => {
+                if let Window::Main = self.screen.window {
+                    self.screen.window = Window::Command {
+                        input: String::new(),
+                        history: None,
+                        error: None,
+                        candidates: Vec::new(),
+                    }
+                }
+            }
             AppMessage::WindowChangePDR => {
                 if let Window::Detail { tab: _ } = self.screen.window {
                     if let NodeKind::Drone {
@@ -68,7 +305,12 @@ impl MySimulationController {
                         crashed: false,
                     } = kind
                     {
-                        self.screen.window = Window::ChangePdr { pdr }
+                        self.screen.window = Window::ChangePdr {
+                            pdr,
+                            batch: false,
+                            input: String::new(),
+                            error: None,
+                        }
                     }
                 }
             }
@@ -80,16 +322,58 @@ impl MySimulationController {
             AppMessage::WindowDetail => {
                 if let Window::Main = self.screen.window {
                     self.packet_table_state.select_first();
+                    self.history_page = None;
                     self.screen.window = Window::Detail { tab: 0 }
                 }
             }
+            AppMessage::WindowDiagnostics => {
+                if let Window::Main = self.screen.window {
+                    self.packet_table_state.select_first();
+                    self.screen.window = Window::Diagnostics;
+                }
+            }
+            AppMessage::WindowFloods => {
+                if let Window::Main = self.screen.window {
+                    self.packet_table_state.select_first();
+                    self.screen.window = Window::Floods;
+                }
+            }
+            AppMessage::WindowNacks => {
+                if let Window::Main = self.screen.window {
+                    self.packet_table_state.select_first();
+                    self.screen.window = Window::Nacks {
+                        grouping: NackGrouping::Emitter,
+                    };
+                }
+            }
+            AppMessage::WindowReportCard => {
+                if let Window::Main = self.screen.window {
+                    self.screen.window = Window::ReportCard;
+                }
+            }
+            AppMessage::WindowMetrics => {
+                if let Window::Main = self.screen.window {
+                    self.screen.window = Window::Metrics { per_node: false }
+                }
+            }
             AppMessage::Done => match self.screen.window {
                 Window::Main => {}
                 Window::Error { message: _ } => {
                     self.reset_list();
                     self.screen.window = Window::Main;
                 }
-                Window::Move | Window::Detail { tab: _ } => self.screen.window = Window::Main,
+                Window::Move
+                | Window::Detail { tab: _ }
+                | Window::Metrics { .. }
+                | Window::ReportCard
+                | Window::Diagnostics
+                | Window::Floods
+                | Window::Nacks { .. } => {
+                    self.screen.window = Window::Main;
+                }
+                Window::Route { tab, .. } => {
+                    self.screen.window = Window::Detail { tab };
+                }
                 Window::AddConnection { origin } => {
                     info!("received AppMessage::Done, current window is AddConnection, adding connection...");
                     match self.add_connection(origin, id) {
@@ -104,40 +388,147 @@ impl MySimulationController {
                         }
                     };
                 }
-                Window::ChangePdr { pdr } => {
-                    self.change_pdr(pdr);
-                    self.screen.window = Window::Detail { tab: 0 };
+                Window::ChangePdr {
+                    pdr,
+                    batch,
+                    ref input,
+                    ref mut error,
+                } => {
+                    // a typed value takes the place of the one chosen with the arrows
+                    let pdr = if input.is_empty() {
+                        pdr
+                    } else {
+                        match command::parse_pdr(input) {
+                            Ok(pdr) => pdr,
+                            Err(e) => {
+                                debug!("typed pdr is not valid: {e}");
+                                *error = Some(e);
+                                return;
+                            }
+                        }
+                    };
+                    if batch {
+                        let marked: Vec<NodeId> = self.screen.marked.iter().copied().collect();
+                        match self.change_pdrs(&marked, pdr) {
+                            Ok(()) => self.screen.window = Window::Main,
+                            Err(message) => self.screen.window = Window::Error { message },
+                        }
+                    } else {
+                        // the drone may have crashed while the window was open
+                        match self.change_pdrs(&[id], pdr) {
+                            Ok(()) => self.screen.window = Window::Detail { tab: 0 },
+                            Err(message) => self.screen.window = Window::Error { message },
+                        }
+                    }
+                }
+                Window::Command { ref input, .. } => {
+                    let input = input.trim().to_string();
+                    let res = self.execute_command(&input);
+                    if !input.is_empty() && self.command_history.last() != Some(&input) {
+                        self.command_history.push(input);
+                    }
+                    match res {
+                        Ok(()) => {
+                            info!("command executed successfully, switched back to Window::Main");
+                            self.screen.window = Window::Main;
+                        }
+                        Err(e) => {
+                            debug!("command failed: {e}");
+                            if let Window::Command {
+                                ref mut error,
+                                ref mut candidates,
+                                ..
+                            } = self.screen.window
+                            {
+                                *error = Some(e);
+                                candidates.clear();
+                            }
+                        }
+                    }
                 }
             },
             // List movement
             AppMessage::ScrollUp => match self.screen.window {
-                Window::Main | Window::AddConnection { .. } => {
+                Window::Main | Window::AddConnection { .. } | Window::Metrics { .. } => {
                     self.scroll_list(true);
                 }
-                Window::Detail { .. } => {
+                Window::Detail { .. }
+                | Window::Diagnostics
+                | Window::Floods
+                | Window::Nacks { .. } => {
                     self.packet_table_state.scroll_up_by(1);
                 }
-                Window::ChangePdr { ref mut pdr } => {
+                Window::ChangePdr {
+                    ref mut pdr,
+                    ref mut input,
+                    ref mut error,
+                    ..
+                } => {
+                    // the arrows discard the typed value
+                    input.clear();
+                    *error = None;
                     *pdr += 0.01;
                     if *pdr > 1.0 {
                         *pdr = 1.0;
                     }
                 }
+                Window::Command {
+                    ref mut input,
+                    ref mut history,
+                    ..
+                } => {
+                    // go back in the command history
+                    let pos = match *history {
+                        Some(pos) => pos.saturating_sub(1),
+                        None => self.command_history.len().saturating_sub(1),
+                    };
+                    if let Some(old) = self.command_history.get(pos) {
+                        input.clone_from(old);
+                        *history = Some(pos);
+                    }
+                }
                 _ => {}
             },
             AppMessage::ScrollDown => match self.screen.window {
-                Window::Main | Window::AddConnection { .. } => {
+                Window::Main | Window::AddConnection { .. } | Window::Metrics { .. } => {
                     self.scroll_list(false);
                 }
-                Window::Detail { .. } => {
+                Window::Detail { .. }
+                | Window::Diagnostics
+                | Window::Floods
+                | Window::Nacks { .. } => {
                     self.packet_table_state.scroll_down_by(1);
                 }
-                Window::ChangePdr { ref mut pdr } => {
+                Window::ChangePdr {
+                    ref mut pdr,
+                    ref mut input,
+                    ref mut error,
+                    ..
+                } => {
+                    // the arrows discard the typed value
+                    input.clear();
+                    *error = None;
                     *pdr -= 0.01;
                     if *pdr < 0.0 {
                         *pdr = 0.0;
                     }
                 }
+                Window::Command {
+                    ref mut input,
+                    ref mut history,
+                    ..
+                } => {
+                    // go forward in the command history, past the last one the input is cleared
+                    if let Some(pos) = *history {
+                        if let Some(newer) = self.command_history.get(pos + 1) {
+                            input.clone_from(newer);
+                            *history = Some(pos + 1);
+                        } else {
+                            input.clear();
+                            *history = None;
+                        }
+                    }
+                }
                 _ => {}
             },
             // Node movement
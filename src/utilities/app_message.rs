@@ -1,18 +1,44 @@
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum AppMessage {
     // used in move node and add node
     MoveNode { x: i8, y: i8 },
 
-    // used in Detail
+    // used in Detail, Metrics
     ChangeTab,
 
+    // used in Route
+    PreviousHop,
+    NextHop,
+
+    // used in command prompt
+    InputChar(char),
+    DeleteChar,
+    Complete,
+    Cancel,
+
     // used in main
     WindowAddConnection,
+    ToggleMark,
+    ClearMarks,
+    WindowChangePDRMarked,
+    CrashMarked,
+    ConnectMarked,
+    WindowCommand,
     WindowChangePDR,
     WindowMove,
     WindowDetail,
+    WindowMetrics,
+    WindowDiagnostics,
+    WindowFloods,
+    WindowNacks,
+    WindowReportCard,
     SpawnDrone,
+    ExportStats,
     Crash,
+    Revive,
+    WindowRoute,
+    OlderPage,
+    NewerPage,
 
     // used in main, add connection
     ScrollUp,
@@ -22,5 +48,6 @@ pub enum AppMessage {
     Done,
 
     // used in all
+    ToggleHelp,
     Quit,
 }
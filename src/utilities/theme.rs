@@ -9,7 +9,9 @@ pub const DRONE_COLOR: Color = Color::LightBlue;
 pub const SERVER_COLOR: Color = Color::LightMagenta;
 pub const CLIENT_COLOR: Color = Color::Cyan;
 pub const CRASH_COLOR: Color = Color::Red;
+pub const FAILED_COLOR: Color = Color::DarkGray;
 pub const ADD_EDGE_COLOR: Color = Color::Green;
+pub const MARKED_COLOR: Color = Color::LightGreen;
 
 pub const PACKET_NACK_COLOR: Color = CRASH_COLOR;
 pub const PACKET_ACK_COLOR: Color = ADD_EDGE_COLOR;
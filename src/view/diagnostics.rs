@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph, Row, StatefulWidget, Table, TableState, Wrap};
+
+use crate::diagnostics::Incident;
+use crate::utilities::theme::{BG_COLOR, CRASH_COLOR, HIGHLIGHT_COLOR, TEXT_COLOR};
+
+/// renders the list of incidents, newest first, and below it the event of the selected one
+pub fn render_diagnostics(
+    incidents: &VecDeque<Incident>,
+    table_state: &mut TableState,
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let [top, bottom] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+
+    let rows: Vec<Row> = incidents
+        .iter()
+        .map(|incident| {
+            Row::new(vec![
+                format!("{}s ago", incident.at.elapsed().as_secs()),
+                incident
+                    .source
+                    .map_or_else(|| "?".to_string(), |id| format!("#{id}")),
+                incident.message.clone(),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["when", "node", "incident"]).style(Style::default().fg(HIGHLIGHT_COLOR)))
+    .row_highlight_style(Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR))
+    .block(
+        Block::bordered()
+            .title(format!("Incidents ({})", incidents.len()))
+            .border_style(Style::default().fg(CRASH_COLOR)),
+    )
+    .bg(BG_COLOR)
+    .fg(TEXT_COLOR);
+    StatefulWidget::render(table, top, frame.buffer_mut(), table_state);
+
+    let event = table_state
+        .selected()
+        .and_then(|i| incidents.get(i))
+        .map_or("no incidents", |incident| incident.event.as_str());
+    Paragraph::new(event)
+        .wrap(Wrap { trim: false })
+        .block(Block::bordered().title("Event"))
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR)
+        .render(bottom, frame.buffer_mut());
+}
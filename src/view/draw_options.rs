@@ -1,16 +1,15 @@
 use std::{collections::HashMap, time::Instant};
 
-use messages::node_event::EventNetworkGraph;
 use ratatui::style::{Color, Style, Stylize};
 use wg_2024::{network::NodeId, packet::PacketType};
 
 use crate::{
-    network::node_representation::NodeRepresentation,
+    network::{flood::Flood, node_representation::NodeRepresentation, topology_diff::TopologyDiff},
     screen::{Screen, Window},
     utilities::theme::{
-        ADD_EDGE_COLOR, BG_COLOR, CLIENT_COLOR, DRONE_COLOR, HIGHLIGHT_COLOR, PACKET_ACK_COLOR,
-        PACKET_FLOOD_REQUEST_COLOR, PACKET_FLOOD_RESPONSE_COLOR, PACKET_FRAGMENT_COLOR,
-        PACKET_NACK_COLOR, SERVER_COLOR, TEXT_COLOR,
+        ADD_EDGE_COLOR, BG_COLOR, CRASH_COLOR, FAILED_COLOR, HIGHLIGHT_COLOR, MARKED_COLOR,
+        PACKET_ACK_COLOR, PACKET_FLOOD_REQUEST_COLOR, PACKET_FLOOD_RESPONSE_COLOR,
+        PACKET_FRAGMENT_COLOR, PACKET_NACK_COLOR, TEXT_COLOR,
     },
     Network,
 };
@@ -48,7 +47,7 @@ fn active_edge_color(x: &Option<(PacketType, Instant)>) -> Color {
     }
 }
 impl DrawGraphOptions {
-    pub fn from_noderepr(n: &NodeRepresentation) -> Self {
+    pub fn from_noderepr(network: &Network, n: &NodeRepresentation) -> Self {
         let mut lines_front = HashMap::new();
         let mut nodes: HashMap<u8, DrawNodeOptions> = HashMap::new();
         nodes.insert(
@@ -61,14 +60,14 @@ impl DrawGraphOptions {
             },
         );
 
-        let count = n.adj.len();
-        for (i, k) in n.adj.iter().enumerate() {
+        let count = network.graph().degree(n.id);
+        for (i, k) in network.graph().neighbors(n.id).enumerate() {
             let angle = 2.0 * std::f64::consts::PI * i as f64 / count as f64;
             let x = 100.0 * angle.cos();
             let y = 100.0 * angle.sin();
 
             nodes.insert(
-                *k,
+                k,
                 DrawNodeOptions {
                     x,
                     y,
@@ -76,7 +75,7 @@ impl DrawGraphOptions {
                     label: format!("(#{k})"),
                 },
             );
-            lines_front.insert((n.id, *k), TEXT_COLOR);
+            lines_front.insert((n.id, k), TEXT_COLOR);
         }
 
         DrawGraphOptions {
@@ -86,43 +85,157 @@ impl DrawGraphOptions {
             nodes,
         }
     }
-    pub fn from_topology(top: &EventNetworkGraph) -> Self {
-        let lines_back = HashMap::new();
+    /// draws the topology known by the node `owner` in the positions of the main canvas, nodes
+    /// and edges that are known correctly are drawn as usual, missing ones are dimmed, known
+    /// edges that do not exist and crashed nodes believed alive are red, nodes with the wrong type
+    /// are highlighted
+    ///
+    /// nodes that are known but not in the network have no position, so they are not drawn
+    pub fn from_topology_diff(network: &Network, owner: NodeId, diff: &TopologyDiff) -> Self {
+        let mut lines_back = HashMap::new();
         let mut lines_front = HashMap::new();
-        let mut nodes = HashMap::new();
+        let mut nodes: HashMap<NodeId, DrawNodeOptions> = HashMap::new();
 
-        for n in &top.nodes {
-            let style;
-            let label;
+        for n in network.nodes() {
+            let mut style = if diff.crashed_nodes.contains(&n.id) {
+                Style::default().bg(CRASH_COLOR).fg(TEXT_COLOR)
+            } else if diff.wrong_types.contains_key(&n.id) {
+                Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR)
+            } else if diff.missing_nodes.contains(&n.id) {
+                Style::default().bg(FAILED_COLOR).fg(TEXT_COLOR)
+            } else {
+                Style::default().bg(n.color()).fg(TEXT_COLOR)
+            };
+            if n.id == owner {
+                style = style.bold().underlined();
+            }
+            nodes.insert(
+                n.id,
+                DrawNodeOptions {
+                    x: f64::from(n.x),
+                    y: f64::from(n.y),
+                    style,
+                    label: n.short_label(),
+                },
+            );
+        }
 
-            match n.node_type {
-                wg_2024::packet::NodeType::Client => {
-                    label = "(C)".to_owned();
-                    style = Style::new().bg(CLIENT_COLOR);
-                }
-                wg_2024::packet::NodeType::Drone => {
-                    label = "(D)".to_owned();
-                    style = Style::new().bg(DRONE_COLOR);
-                }
-                wg_2024::packet::NodeType::Server => {
-                    label = "(S)".to_owned();
-                    style = Style::new().bg(SERVER_COLOR);
-                }
+        for edge in &diff.missing_edges {
+            lines_back.insert(*edge, FAILED_COLOR);
+        }
+        for edge in &diff.correct_edges {
+            lines_front.insert(*edge, TEXT_COLOR);
+        }
+        for (from, to) in &diff.stale_edges {
+            if nodes.contains_key(from) && nodes.contains_key(to) {
+                lines_front.insert((*from, *to), CRASH_COLOR);
+            }
+        }
+
+        DrawGraphOptions {
+            padding: 0.0,
+            lines_front,
+            lines_back,
+            nodes,
+        }
+    }
+
+    /// draws the network dimmed with the route on top of it, the hops up to `step` are highlighted
+    /// and hops that are not edges of the network anymore are red, the node the packet is at is
+    /// marked and the node reached with `step` is highlighted
+    pub fn from_route(network: &Network, hops: &[NodeId], hop_index: usize, step: usize) -> Self {
+        let mut lines_back = HashMap::new();
+        let mut lines_front = HashMap::new();
+        let mut nodes: HashMap<NodeId, DrawNodeOptions> = HashMap::new();
+
+        for n in network.nodes() {
+            let style = if hops.get(step) == Some(&n.id) {
+                Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR).bold()
+            } else if hops.get(hop_index) == Some(&n.id) {
+                Style::default().bg(MARKED_COLOR).fg(BG_COLOR)
+            } else {
+                Style::default().bg(n.color()).fg(TEXT_COLOR)
+            };
+            nodes.insert(
+                n.id,
+                DrawNodeOptions {
+                    x: f64::from(n.x),
+                    y: f64::from(n.y),
+                    style,
+                    label: n.short_label(),
+                },
+            );
+        }
+
+        for ((from, to), _) in network.graph().edges() {
+            lines_back.insert((from, to), FAILED_COLOR);
+        }
+        let valid = network.route_hops_valid(hops);
+        for (i, pair) in hops.windows(2).enumerate() {
+            let (from, to) = (pair[0], pair[1]);
+            // hops to nodes that are not in the network cannot be drawn
+            if !nodes.contains_key(&from) || !nodes.contains_key(&to) {
+                continue;
             }
+            let color = if !valid[i + 1] {
+                CRASH_COLOR
+            } else if i < step {
+                HIGHLIGHT_COLOR
+            } else {
+                TEXT_COLOR
+            };
+            lines_back.remove(&(to, from));
+            lines_front.insert((from, to), color);
+        }
+
+        DrawGraphOptions {
+            padding: 0.0,
+            lines_front,
+            lines_back,
+            nodes,
+        }
+    }
 
-            let id = n.node_id;
+    /// draws the network dimmed, with the propagation tree of the flood on top of it and the
+    /// paths of the responses on top of the tree, the initiator is highlighted and the nodes the
+    /// flood did not reach are dimmed
+    pub fn from_flood(network: &Network, flood: &Flood) -> Self {
+        let mut lines_back = HashMap::new();
+        let mut lines_front = HashMap::new();
+        let mut nodes: HashMap<NodeId, DrawNodeOptions> = HashMap::new();
+
+        for n in network.nodes() {
+            let style = if n.id == flood.initiator {
+                Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR).bold()
+            } else if flood.node_types.contains_key(&n.id) {
+                Style::default().bg(n.color()).fg(TEXT_COLOR)
+            } else {
+                Style::default().bg(FAILED_COLOR).fg(TEXT_COLOR)
+            };
             nodes.insert(
-                id,
+                n.id,
                 DrawNodeOptions {
-                    x: f64::from(id) * 5.,
-                    y: f64::from(id) % 3. * 5.,
+                    x: f64::from(n.x),
+                    y: f64::from(n.y),
                     style,
-                    label,
+                    label: n.short_label(),
                 },
             );
+        }
 
-            for nghb in &n.neighbors {
-                lines_front.insert((id, *nghb), TEXT_COLOR);
+        for ((from, to), _) in network.graph().edges() {
+            lines_back.insert((from, to), FAILED_COLOR);
+        }
+        // the flood may mention nodes that are not in the network anymore
+        let drawable =
+            |(from, to): &(NodeId, NodeId)| nodes.contains_key(from) && nodes.contains_key(to);
+        for edge in flood.tree_edges().filter(drawable) {
+            lines_front.insert(edge, PACKET_FLOOD_REQUEST_COLOR);
+        }
+        for path in &flood.responses {
+            for edge in path.windows(2).map(|w| (w[0], w[1])).filter(drawable) {
+                lines_front.remove(&(edge.1, edge.0));
+                lines_front.insert(edge, PACKET_FLOOD_RESPONSE_COLOR);
             }
         }
 
@@ -143,7 +256,7 @@ impl DrawGraphOptions {
 
         let front_color = match screen.window {
             Window::AddConnection { .. } | Window::Move => ADD_EDGE_COLOR,
-            Window::Main => TEXT_COLOR,
+            Window::Main | Window::Command { .. } => TEXT_COLOR,
             _ => unreachable!(),
         };
 
@@ -154,32 +267,39 @@ impl DrawGraphOptions {
             }
         }
 
-        for ((from, to), x) in &network.edges {
-            if *from == id || *to == id {
+        // edges of failed nodes are drawn behind everything else
+        for (from, to) in network.graph().failed_edges() {
+            lines_back.insert((from, to), FAILED_COLOR);
+        }
+        for ((from, to), edge) in network.graph().edges() {
+            if from == id || to == id {
                 match screen.window {
-                    Window::Main | Window::Move => {
-                        lines_front.insert((*from, *to), active_edge_color(x));
+                    Window::Main | Window::Move | Window::Command { .. } => {
+                        lines_front.insert((from, to), active_edge_color(&edge.last_packet));
                     }
                     Window::AddConnection { .. } => {
-                        lines_back.insert((*from, *to), active_edge_color(x));
+                        lines_back.insert((from, to), active_edge_color(&edge.last_packet));
                     }
                     _ => unreachable!(),
                 }
             } else {
-                lines_back.insert((*from, *to), active_edge_color(x));
+                lines_back.insert((from, to), active_edge_color(&edge.last_packet));
             };
         }
-        for n in &network.nodes {
+        for n in network.nodes() {
             // special coloring
             let selected_index = screen.focus;
             let mut style = Style::default();
             match screen.window {
                 // highlight selected node
-                Window::Main | Window::Move => {
+                Window::Main | Window::Move | Window::Command { .. } => {
                     if selected_index == n.id {
                         style = style.bg(HIGHLIGHT_COLOR);
                         style = style.fg(BG_COLOR);
                         style = style.bold();
+                    } else if screen.marked.contains(&n.id) {
+                        style = style.bg(MARKED_COLOR);
+                        style = style.fg(BG_COLOR);
                     } else {
                         style = style.bg(n.color());
                         style = style.fg(TEXT_COLOR);
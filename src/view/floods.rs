@@ -0,0 +1,128 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph, Row, StatefulWidget, Table, TableState, Wrap};
+
+use crate::network::node_kind::NodeKind;
+use crate::network::Network;
+use crate::utilities::theme::{
+    BG_COLOR, HIGHLIGHT_COLOR, PACKET_FLOOD_REQUEST_COLOR, PACKET_FLOOD_RESPONSE_COLOR, TEXT_COLOR,
+};
+
+use super::draw_options::DrawGraphOptions;
+use super::simulation::render_simulation;
+
+/// renders the list of floods, newest first, with the propagation tree of the selected one on
+/// the canvas and its summary below the list
+pub fn render_floods(
+    network: &Network,
+    table_state: &mut TableState,
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let [left, right] = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+    let [top, bottom] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(left);
+    let floods = &network.floods.floods;
+
+    let rows: Vec<Row> = floods
+        .iter()
+        .map(|flood| {
+            Row::new(vec![
+                format!("#{}", flood.initiator),
+                flood.flood_id.to_string(),
+                format!("{:.2}s", flood.duration().as_secs_f32()),
+                flood.drones_reached().to_string(),
+                flood.redundant.len().to_string(),
+                flood.responses.len().to_string(),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(9),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(6),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ],
+    )
+    .header(
+        Row::new([
+            "initiator",
+            "flood",
+            "duration",
+            "drones",
+            "redundant",
+            "responses",
+        ])
+        .style(Style::default().fg(HIGHLIGHT_COLOR)),
+    )
+    .row_highlight_style(Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR))
+    .block(Block::bordered().title(format!("Floods ({})", floods.len())))
+    .bg(BG_COLOR)
+    .fg(TEXT_COLOR);
+    StatefulWidget::render(table, top, frame.buffer_mut(), table_state);
+
+    let Some(flood) = table_state.selected().and_then(|i| floods.get(i)) else {
+        Paragraph::new("no floods")
+            .block(Block::bordered().title("Flood"))
+            .bg(BG_COLOR)
+            .fg(TEXT_COLOR)
+            .render(bottom, frame.buffer_mut());
+        return;
+    };
+
+    let drones = network
+        .nodes()
+        .iter()
+        .filter(|n| matches!(n.kind, NodeKind::Drone { .. }))
+        .count();
+    let mut lines = vec![
+        Line::from(format!(
+            "initiator #{}, {} requests in {:.2}s",
+            flood.initiator,
+            flood.requests,
+            flood.duration().as_secs_f32()
+        )),
+        Line::from(format!(
+            "reached {} of {drones} drones",
+            flood.drones_reached()
+        )),
+        Line::from(format!(
+            "redundant forwards: {}",
+            flood
+                .redundant
+                .iter()
+                .map(|(from, to)| format!("#{from}->#{to}"))
+                .collect::<Vec<String>>()
+                .join(" ")
+        )),
+        Line::styled("tree", Style::default().fg(PACKET_FLOOD_REQUEST_COLOR)),
+    ];
+    for path in &flood.responses {
+        let path = path
+            .iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<String>>()
+            .join(" -> ");
+        lines.push(Line::styled(
+            format!("response {path}"),
+            Style::default().fg(PACKET_FLOOD_RESPONSE_COLOR),
+        ));
+    }
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title(format!("Flood {}", flood.flood_id)))
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR)
+        .render(bottom, frame.buffer_mut());
+
+    let block = Block::bordered().title("Propagation").bg(BG_COLOR);
+    let inner = block.inner(right);
+    block.render(right, frame.buffer_mut());
+    render_simulation(
+        &DrawGraphOptions::from_flood(network, flood),
+        inner,
+        frame.buffer_mut(),
+    );
+}
@@ -1,44 +1,62 @@
-use super::keys::{
-    DETAIL_KEYS_DRONE, DETAIL_KEYS_NOTDRONE, ERROR_KEYS, MAIN_KEYS, MAIN_KEYS_ADD_CONNECTION,
-    MOVE_KEYS, PDR_KEYS,
-};
 use crate::{
-    screen::{Screen, Window},
-    utilities::theme::{BOTTOMPANEL_BG, HIGHLIGHT_COLOR, INVERTED_TEXT_COLOR, TEXT_COLOR},
+    keypress_handler::bindings::active_bindings,
+    pipeline::Hud,
+    screen::Screen,
+    utilities::theme::{
+        BOTTOMPANEL_BG, CRASH_COLOR, HIGHLIGHT_COLOR, INVERTED_TEXT_COLOR, TEXT_COLOR,
+    },
 };
 
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
     widgets::Widget,
 };
 
-use crate::{network::node_kind::NodeKind, Network};
+use crate::Network;
+
+/// events waiting in the channels above which the backlog is highlighted
+const BACKLOG_WARNING: usize = 10_000;
 
-pub fn render_footer(_network: &Network, screen: &Screen, area: Rect, buf: &mut Buffer) {
-    let keys: &[(&str, &str)] = match screen.window {
-        Window::Main => &MAIN_KEYS,
-        Window::Move => &MOVE_KEYS,
-        Window::AddConnection { origin: _ } => &MAIN_KEYS_ADD_CONNECTION,
-        Window::ChangePdr { pdr: _ } => &PDR_KEYS,
-        Window::Detail { tab: _ } => match screen.kind {
-            NodeKind::Drone { pdr: _, crashed: _ } => &DETAIL_KEYS_DRONE,
-            NodeKind::Client | NodeKind::Server => &DETAIL_KEYS_NOTDRONE,
-        },
-        Window::Error { message: _ } => &ERROR_KEYS,
+/// renders the hints of the active keys, with the ingestion rate, the channel backlog and the
+/// frame rate on the right
+pub fn render_footer(_network: &Network, screen: &Screen, hud: &Hud, area: Rect, buf: &mut Buffer) {
+    let backlog_style = if hud.backlog > BACKLOG_WARNING {
+        Style::new().fg(CRASH_COLOR).bg(BOTTOMPANEL_BG)
+    } else {
+        Style::new().fg(TEXT_COLOR).bg(BOTTOMPANEL_BG)
     };
+    let hud = Line::from(vec![
+        Span::styled(
+            format!(" {:.0} ev/s ", hud.events_per_sec),
+            Style::new().fg(TEXT_COLOR).bg(BOTTOMPANEL_BG),
+        ),
+        Span::styled(format!("backlog {} ", hud.backlog), backlog_style),
+        Span::styled(
+            format!("{:.0} fps ", hud.frames_per_sec),
+            Style::new().fg(TEXT_COLOR).bg(BOTTOMPANEL_BG),
+        ),
+    ]);
+    let [area, hud_area] = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Length(u16::try_from(hud.width()).unwrap_or(u16::MAX)),
+    ])
+    .areas(area);
+    hud.right_aligned()
+        .style(BOTTOMPANEL_BG)
+        .render(hud_area, buf);
 
-    let spans: Vec<Span> = keys
+    let spans: Vec<Span> = active_bindings(screen)
         .iter()
-        .flat_map(|(key, desc)| {
+        .flat_map(|binding| {
             let key = Span::styled(
-                format!(" {key} "),
+                format!(" {} ", binding.label),
                 Style::new().fg(INVERTED_TEXT_COLOR).bg(HIGHLIGHT_COLOR),
             );
             let desc = Span::styled(
-                format!(" {desc} "),
+                format!(" {} ", binding.hint),
                 Style::new().fg(TEXT_COLOR).bg(BOTTOMPANEL_BG),
             );
             [key, desc]
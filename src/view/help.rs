@@ -0,0 +1,90 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, Row, Table, Widget},
+    Frame,
+};
+
+use crate::{
+    keypress_handler::bindings::window_bindings,
+    network::node_kind::NodeKind,
+    screen::{Screen, Window},
+    utilities::theme::{BG_COLOR, HIGHLIGHT_COLOR, INVERTED_TEXT_COLOR, TEXT_COLOR},
+};
+
+/// renders on top of the current window a popup listing every key available in it, with a
+/// description of the action
+pub fn render_help(screen: &Screen, area: Rect, frame: &mut Frame) {
+    let bindings = window_bindings(screen);
+
+    let height = u16::try_from(bindings.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2);
+    let vertical = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(height),
+        Constraint::Fill(1),
+    ]);
+    let horizontal = Layout::horizontal([
+        Constraint::Fill(1),
+        Constraint::Max(90),
+        Constraint::Fill(1),
+    ]);
+    let [_, area, _] = vertical.areas(area);
+    let [_, area, _] = horizontal.areas(area);
+
+    let title = match (&screen.window, screen.kind) {
+        (Window::Main, _) => "Help: Main",
+        (Window::Move, _) => "Help: Move node",
+        (Window::AddConnection { .. }, _) => "Help: Add connection",
+        (Window::ChangePdr { .. }, _) => "Help: Change PDR",
+        (Window::Error { .. }, _) => "Help: Error",
+        (Window::Command { .. }, _) => "Help: Command prompt",
+        (Window::Metrics { .. }, _) => "Help: Metrics",
+        (Window::ReportCard, _) => "Help: Report card",
+        (Window::Diagnostics, _) => "Help: Incidents",
+        (Window::Floods, _) => "Help: Floods",
+        (Window::Nacks { .. }, _) => "Help: Nacks",
+        (Window::Route { .. }, _) => "Help: Route",
+        (Window::Detail { .. }, NodeKind::Drone { .. }) => "Help: Drone detail",
+        (Window::Detail { .. }, NodeKind::Client) => "Help: Client detail",
+        (Window::Detail { .. }, NodeKind::Server) => "Help: Server detail",
+    };
+
+    let block = Block::bordered()
+        .title(title)
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR));
+
+    let width = bindings
+        .iter()
+        .map(|b| b.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows: Vec<Row> = bindings
+        .iter()
+        .map(|b| {
+            Row::new(vec![
+                Cell::from(Line::from(Span::styled(
+                    format!(" {} ", b.label),
+                    Style::new().fg(INVERTED_TEXT_COLOR).bg(HIGHLIGHT_COLOR),
+                ))),
+                Cell::from(b.description),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(u16::try_from(width + 2).unwrap_or(u16::MAX)),
+            Constraint::Fill(1),
+        ],
+    )
+    .column_spacing(1)
+    .block(block);
+
+    Clear.render(area, frame.buffer_mut());
+    frame.render_widget(table, area);
+}
@@ -1,9 +1,13 @@
-use crate::{screen::Screen, utilities::theme::{BG_COLOR, HIGHLIGHT_COLOR, TEXT_COLOR}};
+use crate::{
+    screen::Screen,
+    utilities::theme::{BG_COLOR, CRASH_COLOR, HIGHLIGHT_COLOR, MARKED_COLOR, TEXT_COLOR},
+};
 
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, Borders, HighlightSpacing, List, ListDirection, ListState, StatefulWidget},
 };
 
@@ -16,18 +20,33 @@ pub fn render_list(
     area: Rect,
     buf: &mut Buffer,
 ) {
-    let _ = screen;
+    let title = if screen.marked.is_empty() {
+        "Nodes".to_string()
+    } else {
+        format!("Nodes ({} marked)", screen.marked.len())
+    };
     let left_block = Block::new()
         .borders(Borders::TOP | Borders::LEFT)
-        .title("Nodes")
+        .title(title)
         .bg(BG_COLOR)
         .fg(TEXT_COLOR);
 
     let items = network
-        .nodes
+        .nodes()
         .iter()
-        .map(|x| x.thread_name.to_string())
-        .collect::<Vec<String>>();
+        .map(|x| {
+            let mut line = if screen.marked.contains(&x.id) {
+                Line::styled(format!("*{}", x.thread_name), Style::new().fg(MARKED_COLOR))
+            } else {
+                Line::from(x.thread_name.to_string())
+            };
+            // drones that do not seem to follow the protocol when dropping
+            if x.drop_check.is_suspicious() {
+                line.push_span(Span::styled(" !", Style::new().fg(CRASH_COLOR).bold()));
+            }
+            line
+        })
+        .collect::<Vec<Line>>();
     //let items = ["Drone  #12321","Drone  #12321","Drone  #12321","Drone  #12321", "Client #22343", "Server #32342"];
     let list = List::new(items)
         .block(Block::bordered().title("List"))
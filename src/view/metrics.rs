@@ -0,0 +1,154 @@
+use std::time::Instant;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, ListState, Padding};
+
+use crate::network::throughput::{Sample, NACK_NAMES};
+use crate::network::Network;
+use crate::screen::Screen;
+use crate::utilities::theme::{
+    BG_COLOR, DISCOVERY_MSG, HIGHLIGHT_COLOR, MEDIA_MSG, PACKET_ACK_COLOR,
+    PACKET_FLOOD_REQUEST_COLOR, PACKET_FRAGMENT_COLOR, PACKET_NACK_COLOR, TEXT_COLOR,
+};
+
+use super::list::render_list;
+
+/// seconds shown on the x axis of the charts
+const METRICS_WINDOW_SECS: u64 = 120;
+
+const NACK_COLORS: [Color; 4] = [PACKET_NACK_COLOR, Color::LightRed, Color::Yellow, MEDIA_MSG];
+
+/// one line of a chart, with the points already computed
+struct Series {
+    name: String,
+    color: Color,
+    points: Vec<(f64, f64)>,
+}
+
+impl Series {
+    fn new(name: &str, color: Color, samples: &[Sample], value: impl Fn(&Sample) -> u64) -> Self {
+        // the last sample is the current second, drawn at x = 0
+        let last = samples.len().saturating_sub(1);
+        let points = samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ((i as f64) - (last as f64), value(s) as f64))
+            .collect();
+        Series {
+            name: name.to_string(),
+            color,
+            points,
+        }
+    }
+}
+
+/// renders the node list on the left, used to pick the node of the breakdown, and four charts
+/// with the traffic of the last seconds on the right
+pub fn render_metrics(
+    network: &Network,
+    screen: &Screen,
+    per_node: bool,
+    node_list_state: &mut ListState,
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let [left, right] = Layout::horizontal([Constraint::Max(20), Constraint::Fill(1)]).areas(area);
+    render_list(network, screen, node_list_state, left, frame.buffer_mut());
+
+    let node = per_node.then_some(screen.focus);
+    let title = match node {
+        Some(id) => format!("Metrics of #{id}, last {METRICS_WINDOW_SECS}s"),
+        None => format!("Metrics of the network, last {METRICS_WINDOW_SECS}s"),
+    };
+    let block = Block::new()
+        .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
+        .title(title)
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR)
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(right);
+    block.render(right, frame.buffer_mut());
+
+    let samples = network
+        .throughput
+        .series(node, Instant::now(), METRICS_WINDOW_SECS);
+
+    let [top, bottom] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(inner);
+    let [top_left, top_right] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(top);
+    let [bottom_left, bottom_right] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(bottom);
+
+    let fragments = [Series::new(
+        "fragments",
+        PACKET_FRAGMENT_COLOR,
+        &samples,
+        |s| s.fragments,
+    )];
+    render_chart("Fragments/s", &fragments, top_left, frame);
+
+    let mut acks = vec![Series::new("ack", PACKET_ACK_COLOR, &samples, |s| s.acks)];
+    for (i, name) in NACK_NAMES.iter().enumerate() {
+        acks.push(Series::new(name, NACK_COLORS[i], &samples, |s| s.nacks[i]));
+    }
+    render_chart("Acks and Nacks/s", &acks, top_right, frame);
+
+    let floods = [Series::new(
+        "flood requests",
+        PACKET_FLOOD_REQUEST_COLOR,
+        &samples,
+        |s| s.flood_requests,
+    )];
+    render_chart("Flood requests/s", &floods, bottom_left, frame);
+
+    let messages = [
+        Series::new("completed/s", HIGHLIGHT_COLOR, &samples, |s| {
+            s.messages_completed
+        }),
+        Series::new("active sessions", DISCOVERY_MSG, &samples, |s| {
+            s.active_sessions
+        }),
+    ];
+    render_chart("Messages", &messages, bottom_right, frame);
+}
+
+fn render_chart(title: &str, series: &[Series], area: Rect, frame: &mut Frame) {
+    let max = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|(_, y)| *y))
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let min_x = series
+        .first()
+        .and_then(|s| s.points.first())
+        .map_or(0.0, |(x, _)| *x);
+
+    let datasets = series
+        .iter()
+        .map(|s| {
+            Dataset::default()
+                .name(s.name.as_str())
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(s.color))
+                .data(&s.points)
+        })
+        .collect();
+
+    let chart = Chart::new(datasets)
+        .block(Block::bordered().title(title))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(TEXT_COLOR).dim())
+                .bounds([min_x, 0.0])
+                .labels([format!("{min_x}s"), "now".to_string()]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(TEXT_COLOR).dim())
+                .bounds([0.0, max])
+                .labels(["0".to_string(), format!("{max}")]),
+        )
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    frame.render_widget(chart, area);
+}
@@ -1,9 +1,15 @@
+mod diagnostics;
 mod draw_options;
+mod floods;
 mod footer;
-mod keys;
+mod help;
 mod list;
+mod metrics;
+mod nacks;
 mod node_detail;
 mod packet_formatter;
+mod report_card;
+mod route;
 mod simulation;
 mod stats;
 mod tabs;
@@ -11,44 +17,140 @@ mod tabs;
 use footer::render_footer;
 use list::render_list;
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders, Gauge, ListState, Padding, Paragraph, TableState, Wrap};
+use ratatui::widgets::{
+    Block, Borders, Clear, Gauge, ListState, Padding, Paragraph, TableState, Wrap,
+};
 use simulation::render_simulation;
 use stats::render_stats;
 //use wg_2024::config::{Client, Drone, Server};
+use std::collections::VecDeque;
+use wg_2024::{network::NodeId, packet::PacketType};
 
+use crate::chaos::Chaos;
+use crate::diagnostics::Incident;
+use crate::network::history::{HistoryPage, HistoryRows};
 use crate::network::node_kind::NodeKind;
+use crate::network::reassembly::Session;
 use crate::network::Network;
+use crate::pdr_sweep::PdrSweep;
+use crate::pipeline::Hud;
 use crate::screen::Window;
 use crate::utilities::theme::{BG_COLOR, CRASH_COLOR, HIGHLIGHT_COLOR, TEXT_COLOR};
 use crate::Screen;
 
+#[allow(clippy::too_many_arguments)]
 pub fn render(
     network: &Network,
     screen: &Screen,
+    sweeps: &[PdrSweep],
+    chaos: Option<&Chaos>,
+    incidents: &VecDeque<Incident>,
+    history: Option<&HistoryPage>,
+    hud: &Hud,
     node_list_state: &mut ListState,
     table_state: &mut TableState,
     frame: &mut Frame,
 ) {
     let [main, footer] =
         Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
-    render_footer(network, screen, footer, frame.buffer_mut());
+    render_footer(network, screen, hud, footer, frame.buffer_mut());
 
     match screen.window {
         Window::Error { message } => {
             render_error(message, main, frame);
         }
         Window::Detail { tab } => {
-            render_detail(network, tab, screen, table_state, main, frame);
+            render_detail(
+                network,
+                tab,
+                screen,
+                sweeps,
+                history,
+                table_state,
+                main,
+                frame,
+            );
+        }
+        Window::Metrics { per_node } => {
+            metrics::render_metrics(network, screen, per_node, node_list_state, main, frame);
+        }
+        Window::Diagnostics => {
+            diagnostics::render_diagnostics(incidents, table_state, main, frame);
+        }
+        Window::Floods => {
+            floods::render_floods(network, table_state, main, frame);
+        }
+        Window::Nacks { grouping } => {
+            nacks::render_nacks(network, grouping, table_state, main, frame);
+        }
+        Window::Route {
+            ref hops,
+            hop_index,
+            step,
+            ..
+        } => {
+            route::render_route(network, hops, hop_index, step, main, frame);
+        }
+        Window::ReportCard => {
+            report_card::render_report_card(network, main, frame);
         }
         Window::Main | Window::Move | Window::AddConnection { origin: _ } => {
-            render_standard(network, screen, node_list_state, main, frame);
+            render_standard(
+                network,
+                screen,
+                chaos,
+                incidents.len(),
+                node_list_state,
+                main,
+                frame,
+            );
+        }
+        Window::Command {
+            ref input,
+            ref error,
+            ref candidates,
+            ..
+        } => {
+            render_standard(
+                network,
+                screen,
+                chaos,
+                incidents.len(),
+                node_list_state,
+                main,
+                frame,
+            );
+            render_command(input, error.as_deref(), candidates, main, frame);
         }
-        Window::ChangePdr { pdr } => render_changepdr(pdr, main, frame),
+        Window::ChangePdr {
+            pdr,
+            batch,
+            ref input,
+            ref error,
+        } => {
+            let targets: Vec<NodeId> = if batch {
+                screen.marked.iter().copied().collect()
+            } else {
+                vec![screen.focus]
+            };
+            render_changepdr(pdr, input, error.as_deref(), &targets, main, frame);
+        }
+    }
+
+    if screen.help {
+        help::render_help(screen, main, frame);
     }
 }
 
-fn render_changepdr(pdr: f32, area: Rect, frame: &mut Frame) {
-    let vertical = Layout::vertical([Constraint::Fill(1), Constraint::Max(5), Constraint::Fill(1)]);
+fn render_changepdr(
+    pdr: f32,
+    input: &str,
+    error: Option<&str>,
+    targets: &[NodeId],
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let vertical = Layout::vertical([Constraint::Fill(1), Constraint::Max(6), Constraint::Fill(1)]);
     let horizontal = Layout::horizontal([
         Constraint::Fill(1),
         Constraint::Fill(10),
@@ -57,8 +159,29 @@ fn render_changepdr(pdr: f32, area: Rect, frame: &mut Frame) {
     let [_, area, _] = vertical.areas(area);
     let [_, area, _] = horizontal.areas(area);
 
-    let mut block = Block::bordered();
-    let inner = block.inner(area);
+    let title = targets
+        .iter()
+        .map(|id| format!("#{id}"))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let mut block = Block::bordered().title(format!("PDR of {title}"));
+    let [inner, status_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+    // while typing the gauge previews the typed value, if it is valid
+    let typed = input
+        .parse::<f32>()
+        .ok()
+        .filter(|x| (0.0..=1.0).contains(x));
+    let pdr = typed.unwrap_or(pdr);
+    let status = match (error, input.is_empty()) {
+        (Some(e), _) => Line::styled(e, Style::default().fg(CRASH_COLOR)),
+        (None, false) => Line::styled(format!("typed: {input}"), Style::default().fg(TEXT_COLOR)),
+        (None, true) => Line::styled(
+            "type a value or use ↑/↓",
+            Style::default().fg(TEXT_COLOR).dim(),
+        ),
+    };
     let mut gauge = Gauge::default();
     if pdr >= 0.999999 {
         gauge = gauge.label(Span::from("🦆🦆🦆🦆🦆🦆🦆🦆🦆".to_string()));
@@ -72,7 +195,38 @@ fn render_changepdr(pdr: f32, area: Rect, frame: &mut Frame) {
 
     frame.render_widget(block, area);
     gauge.render(inner, frame.buffer_mut());
+    status.render(status_area, frame.buffer_mut());
 }
+fn render_command(
+    input: &str,
+    error: Option<&str>,
+    candidates: &[String],
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let [_, area] = Layout::vertical([Constraint::Fill(1), Constraint::Length(4)]).areas(area);
+
+    let block = Block::bordered()
+        .title("Command")
+        .bg(BG_COLOR)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR));
+    let inner = block.inner(area);
+
+    let prompt = Line::from(vec![
+        Span::styled(":", Style::default().fg(HIGHLIGHT_COLOR)),
+        Span::styled(input, Style::default().fg(TEXT_COLOR)),
+        Span::styled(" ", Style::default().reversed()),
+    ]);
+    let status = match error {
+        Some(e) => Line::styled(e, Style::default().fg(CRASH_COLOR)),
+        None => Line::styled(candidates.join(" "), Style::default().fg(TEXT_COLOR).dim()),
+    };
+
+    Clear.render(area, frame.buffer_mut());
+    frame.render_widget(block, area);
+    Paragraph::new(vec![prompt, status]).render(inner, frame.buffer_mut());
+}
+
 fn render_error(message: &str, area: Rect, frame: &mut Frame) {
     let vertical = Layout::vertical([Constraint::Fill(1), Constraint::Max(5), Constraint::Fill(1)]);
     let horizontal = Layout::horizontal([
@@ -96,10 +250,13 @@ fn render_error(message: &str, area: Rect, frame: &mut Frame) {
     frame.render_widget(block, area);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_detail(
     network: &Network,
     tab: usize,
     screen: &Screen,
+    sweeps: &[PdrSweep],
+    history: Option<&HistoryPage>,
     table_state: &mut TableState,
     area: Rect,
     frame: &mut Frame,
@@ -129,7 +286,7 @@ fn render_detail(
         ..symbols::border::PLAIN
     };
 
-    let leftborder = Block::new()
+    let mut leftborder = Block::new()
         .border_set(left_border_set)
         .borders(Borders::TOP | Borders::LEFT | Borders::BOTTOM)
         .bg(BG_COLOR)
@@ -145,6 +302,9 @@ fn render_detail(
         .bg(BG_COLOR)
         .fg(TEXT_COLOR);
 
+    if let Some(history) = history {
+        leftborder = leftborder.title_bottom(format!("older {}/{}", history.page, history.pages));
+    }
     let left_inner = leftborder.inner(left);
     let top_inner = topborder.inner(top);
     let bottom_inner = bottomborder.inner(bottom);
@@ -154,29 +314,58 @@ fn render_detail(
     bottomborder.render(bottom, frame.buffer_mut());
 
     tabs::render_tabs(tab, screen.kind, tabs, frame.buffer_mut());
-    tabs::render_tab_content(tab, screen, network, table_state, left_inner, frame);
+    tabs::render_tab_content(
+        tab,
+        screen,
+        network,
+        history,
+        table_state,
+        left_inner,
+        frame,
+    );
 
     let node = network.get_node_from_id(screen.focus).unwrap();
 
-    node_detail::node_detail(node, top_inner, frame);
-
-    if tab == 0 || matches!(screen.kind, NodeKind::Drone { .. }) {
-        let packet = match (tab, screen.kind) {
-            (0, _) => node.sent.get(table_state.selected().unwrap_or(usize::MAX)),
-            (1, NodeKind::Drone { .. }) => node
-                .dropped
-                .get(table_state.selected().unwrap_or(usize::MAX)),
-            (2, NodeKind::Drone { .. }) => node
-                .shortcutted
-                .get(table_state.selected().unwrap_or(usize::MAX)),
+    let sweep = sweeps.iter().find(|s| s.drone == node.id);
+    node_detail::node_detail(network, node, sweep, top_inner, frame);
+
+    let selected = table_state.selected().unwrap_or(usize::MAX);
+    if let Some(HistoryRows::Messages(messages)) = history.map(|h| &h.rows) {
+        let t = match messages.get(selected) {
+            Some((message, _)) => packet_formatter::message_detail(message),
+            None => Paragraph::default(),
+        };
+        t.render(bottom_inner, frame.buffer_mut());
+    } else if tab == 0 || matches!(screen.kind, NodeKind::Drone { .. }) {
+        let packet = match (tab, screen.kind, history.map(|h| &h.rows)) {
+            (_, _, Some(HistoryRows::Packets(packets))) => packets.get(selected),
+            (0, _, _) => node.sent.get(selected),
+            (1, NodeKind::Drone { .. }, _) => node.dropped.get(selected),
+            (2, NodeKind::Drone { .. }, _) => node.shortcutted.get(selected),
             _ => None,
         };
 
+        // fragments are shown next to the session they belong to
+        let session = packet.and_then(|p| match (&p.pack_type, p.routing_header.hops.first()) {
+            (PacketType::MsgFragment(_), Some(source)) => {
+                network.sessions.get(*source, p.session_id)
+            }
+            _ => None,
+        });
         let t = match packet {
             Some(p) => packet_formatter::packet_detail(p),
             None => Paragraph::default(),
         };
-        t.render(bottom_inner, frame.buffer_mut());
+        match session {
+            Some(session) => {
+                let [packet_area, session_area] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+                        .areas(bottom_inner);
+                t.render(packet_area, frame.buffer_mut());
+                render_session(session, session_area, frame);
+            }
+            None => t.render(bottom_inner, frame.buffer_mut()),
+        }
     } else {
         let t = match (tab, screen.kind) {
             (1, NodeKind::Client | NodeKind::Server) => {
@@ -206,9 +395,29 @@ fn render_detail(
     };
 }
 
+/// reassembly progress of the session, followed by the decoded message once it is complete, or
+/// by the message that was sent when the payload cannot be decoded
+fn render_session(session: &Session, area: Rect, frame: &mut Frame) {
+    let block = Block::new()
+        .borders(Borders::LEFT)
+        .padding(Padding::left(1));
+    let inner = block.inner(area);
+    block.render(area, frame.buffer_mut());
+    let [progress, message] =
+        Layout::vertical([Constraint::Length(5), Constraint::Fill(1)]).areas(inner);
+    packet_formatter::session_progress(session).render(progress, frame.buffer_mut());
+    if let Some(Ok(decoded)) = &session.decoded {
+        packet_formatter::message_detail(decoded).render(message, frame.buffer_mut());
+    } else if let Some(sent) = &session.sent {
+        packet_formatter::message_detail(sent).render(message, frame.buffer_mut());
+    }
+}
+
 fn render_standard(
     network: &Network,
     screen: &Screen,
+    chaos: Option<&Chaos>,
+    incidents: usize,
     node_list_state: &mut ListState,
     area: Rect,
     frame: &mut Frame,
@@ -223,10 +432,27 @@ fn render_standard(
         ..symbols::border::PLAIN
     };
 
+    let title = match chaos {
+        Some(chaos) => format!(
+            "Simulation - chaos seed {}, {} faults{}",
+            chaos.options.seed,
+            chaos.history.len(),
+            chaos
+                .history
+                .last()
+                .map(|a| format!(", last: {a}"))
+                .unwrap_or_default()
+        ),
+        None => "Simulation".to_string(),
+    };
+    let title = match incidents {
+        0 => title,
+        n => format!("{title} - {n} incidents"),
+    };
     let block = Block::new()
         .border_set(top_right_border_set)
         .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
-        .title("Simulation")
+        .title(title)
         .bg(BG_COLOR)
         .fg(TEXT_COLOR)
         .padding(Padding::proportional(1));
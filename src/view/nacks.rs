@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph, Row, StatefulWidget, Table, TableState, Wrap};
+use wg_2024::network::NodeId;
+use wg_2024::packet::{NackType, PacketType};
+
+use crate::network::nacks::{NackCounts, NackGroup, NackGrouping};
+use crate::network::Network;
+use crate::utilities::theme::{BG_COLOR, HIGHLIGHT_COLOR, PACKET_NACK_COLOR, TEXT_COLOR};
+
+/// counts of each node, the most frequent first
+fn by_node(counts: &BTreeMap<NodeId, u64>) -> String {
+    let mut counts: Vec<(&NodeId, &u64)> = counts.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1));
+    counts
+        .iter()
+        .map(|(id, n)| format!("#{id}x{n}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn breakdown(counts: &NackCounts) -> Vec<Line<'static>> {
+    vec![
+        Line::from(format!(
+            "ErrorInRouting: {} {}",
+            counts.error_in_routing_total(),
+            by_node(&counts.error_in_routing)
+        )),
+        Line::from(format!(
+            "DestinationIsDrone: {}",
+            counts.destination_is_drone
+        )),
+        Line::from(format!("Dropped: {}", counts.dropped)),
+        Line::from(format!(
+            "UnexpectedRecipient: {} {}",
+            counts.unexpected_recipient_total(),
+            by_node(&counts.unexpected_recipient)
+        )),
+    ]
+}
+
+/// renders the nacks of each group with their counts by type, below it the breakdown of the
+/// selected group and the nacks it is made of, newest first
+pub fn render_nacks(
+    network: &Network,
+    grouping: NackGrouping,
+    table_state: &mut TableState,
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let [top, bottom] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+    let [summary, packets] =
+        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(2)]).areas(bottom);
+    let nacks = &network.nacks;
+    let groups = nacks.groups(grouping);
+
+    let (key_header, title) = match grouping {
+        NackGrouping::Emitter => ("node", "Nacks by node"),
+        NackGrouping::Session => ("session", "Nacks by session"),
+    };
+    let rows: Vec<Row> = groups
+        .iter()
+        .map(|(group, counts)| {
+            Row::new(vec![
+                group.to_string(),
+                counts.error_in_routing_total().to_string(),
+                counts.destination_is_drone.to_string(),
+                counts.dropped.to_string(),
+                counts.unexpected_recipient_total().to_string(),
+                counts.total().to_string(),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(14),
+            Constraint::Length(18),
+            Constraint::Length(7),
+            Constraint::Length(19),
+            Constraint::Length(5),
+        ],
+    )
+    .header(
+        Row::new([
+            key_header,
+            "ErrorInRouting",
+            "DestinationIsDrone",
+            "Dropped",
+            "UnexpectedRecipient",
+            "total",
+        ])
+        .style(Style::default().fg(HIGHLIGHT_COLOR)),
+    )
+    .row_highlight_style(Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR))
+    .block(Block::bordered().title(format!("{title} ({} nacks)", nacks.total.total())))
+    .bg(BG_COLOR)
+    .fg(TEXT_COLOR);
+    StatefulWidget::render(table, top, frame.buffer_mut(), table_state);
+
+    let Some((group, counts)) = table_state.selected().and_then(|i| groups.get(i)) else {
+        Paragraph::new(breakdown(&nacks.total))
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title("All nacks"))
+            .bg(BG_COLOR)
+            .fg(TEXT_COLOR)
+            .render(bottom, frame.buffer_mut());
+        return;
+    };
+
+    let group_title = match group {
+        NackGroup::Emitter(id) => format!("Nacks created by #{id}"),
+        NackGroup::Session { .. } => format!("Nacks of session {group}"),
+    };
+    Paragraph::new(breakdown(counts))
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title(group_title))
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR)
+        .render(summary, frame.buffer_mut());
+
+    let rows: Vec<Row> = nacks
+        .packets_of(*group)
+        .filter_map(|(emitter, packet)| {
+            let PacketType::Nack(nack) = &packet.pack_type else {
+                return None;
+            };
+            let nack_type = match nack.nack_type {
+                NackType::ErrorInRouting(id) => format!("ErrorInRouting({id})"),
+                NackType::DestinationIsDrone => "DestinationIsDrone".to_string(),
+                NackType::Dropped => "Dropped".to_string(),
+                NackType::UnexpectedRecipient(id) => format!("UnexpectedRecipient({id})"),
+            };
+            Some(Row::new(vec![
+                Span::from(format!("#{emitter}")),
+                Span::from(packet.session_id.to_string()),
+                Span::from(nack.fragment_index.to_string()),
+                Span::styled(nack_type, Style::default().fg(PACKET_NACK_COLOR)),
+                Span::from(packet.routing_header.to_string()),
+            ]))
+        })
+        .collect();
+    let shown = rows.len();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(23),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["emitter", "session", "fragment", "type", "route"])
+            .style(Style::default().fg(HIGHLIGHT_COLOR)),
+    )
+    .block(Block::bordered().title(format!("Packets ({shown} kept)")))
+    .bg(BG_COLOR)
+    .fg(TEXT_COLOR);
+    Widget::render(table, packets, frame.buffer_mut());
+}
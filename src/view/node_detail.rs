@@ -1,14 +1,30 @@
 use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Sparkline, SparklineBar};
+use std::time::Instant;
+use wg_2024::network::NodeId;
 //use wg_2024::config::{Client, Drone, Server};
 
+use crate::network::drop_window::DROP_WINDOW_SECS;
 use crate::network::node_kind::NodeKind;
 use crate::network::node_representation::NodeRepresentation;
-use crate::utilities::theme::{CLIENT_COLOR, DRONE_COLOR, SERVER_COLOR};
+use crate::network::topology_diff::TopologyDiff;
+use crate::network::Network;
+use crate::pdr_sweep::PdrSweep;
+use crate::utilities::theme::{
+    ADD_EDGE_COLOR, CLIENT_COLOR, CRASH_COLOR, DRONE_COLOR, FAILED_COLOR, HIGHLIGHT_COLOR,
+    SERVER_COLOR,
+};
 
 use super::draw_options::DrawGraphOptions;
 use super::simulation::render_simulation;
 
-pub fn node_detail(node: &NodeRepresentation, area: Rect, frame: &mut Frame) {
+pub fn node_detail(
+    network: &Network,
+    node: &NodeRepresentation,
+    sweep: Option<&PdrSweep>,
+    area: Rect,
+    frame: &mut Frame,
+) {
     let [left, right] = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
     let mut header = Line::default();
     let nameline = Line::from(format!("Thread name: {}", node.thread_name));
@@ -22,44 +38,208 @@ pub fn node_detail(node: &NodeRepresentation, area: Rect, frame: &mut Frame) {
             content.push_line(format!("crashed:{crashed}"));
             content.push_line(format!("config pdr:{pdr}"));
             content.push_line(format!(
-                "actual pdr:{:.3}",
-                (node.n_frags_dropped as f64) / (node.n_frags_sent as f64),
+                "lifetime pdr:{}",
+                format_rate(
+                    (node.n_frags_sent > 0)
+                        .then(|| node.n_frags_dropped as f64 / node.n_frags_sent as f64)
+                ),
+            ));
+            content.push_line(format!(
+                "last {DROP_WINDOW_SECS}s pdr:{}",
+                format_rate(node.drop_window.rate(Instant::now()))
             ));
             content.push_line(format!(
                 "dropped:{}/{} fragments",
                 node.n_frags_dropped, node.n_frags_sent
             ));
+            for suspicion in node.drop_check.suspicions() {
+                content.push_line(Line::styled(
+                    format!("suspicious: {suspicion}"),
+                    Style::default().fg(CRASH_COLOR),
+                ));
+            }
+            let conformance = &node.conformance;
+            match conformance.recent.front() {
+                Some(last) => content.push_line(Line::styled(
+                    format!(
+                        "protocol: {} violations in {} events, last {last}",
+                        conformance.total_violations(),
+                        conformance.checked
+                    ),
+                    Style::default().fg(CRASH_COLOR),
+                )),
+                None => {
+                    content.push_line(format!("protocol: ok in {} events", conformance.checked))
+                }
+            }
+            if let Some(sweep) = sweep {
+                content.push_line(format!(
+                    "sweep:{sweep} ({:.0}%)",
+                    sweep.progress(Instant::now()) * 100.0
+                ));
+            }
         }
         NodeKind::Client => {
             header.push_span(Span::from("Client").style(Style::default().bg(CLIENT_COLOR)));
             header.push_span(Span::from(format!(" #{}", node.id)));
             content.push_line(header);
             content.push_line(nameline);
+            push_topology_diff(&mut content, network, node);
         }
         NodeKind::Server => {
             header.push_span(Span::from("Server").style(Style::default().bg(SERVER_COLOR)));
             header.push_span(Span::from(format!(" #{}", node.id)));
             content.push_line(header);
             content.push_line(nameline);
+            push_topology_diff(&mut content, network, node);
         }
     }
 
-    content.render(left, frame.buffer_mut());
+    if let Some(failure) = &node.failure {
+        content.push_line(Line::styled(
+            format!("failed: {failure}"),
+            Style::default().fg(CRASH_COLOR),
+        ));
+    }
+
+    if let NodeKind::Drone { pdr, .. } = node.kind {
+        let [text, sparkline] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(6)]).areas(left);
+        content.render(text, frame.buffer_mut());
+        render_drop_sparkline(node, pdr, sparkline, frame);
+    } else {
+        content.render(left, frame.buffer_mut());
+    }
 
     match node.kind {
         NodeKind::Drone { .. } => {
             render_simulation(
-                &DrawGraphOptions::from_noderepr(node),
+                &DrawGraphOptions::from_noderepr(network, node),
                 right,
                 frame.buffer_mut(),
             );
         }
         NodeKind::Client | NodeKind::Server => {
+            let diff = TopologyDiff::new(network, &node.knowntopology);
             render_simulation(
-                &DrawGraphOptions::from_topology(&node.knowntopology),
+                &DrawGraphOptions::from_topology_diff(network, node.id, &diff),
                 right,
                 frame.buffer_mut(),
             );
         }
     }
 }
+
+fn format_ids<'a>(ids: impl Iterator<Item = &'a NodeId>) -> String {
+    ids.map(|id| format!("#{id}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn format_edges<'a>(edges: impl Iterator<Item = &'a (NodeId, NodeId)>) -> String {
+    edges
+        .map(|(a, b)| format!("#{a}-#{b}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// lists how the topology known by the client or server differs from the network, with the
+/// colors used on the canvas
+fn push_topology_diff(content: &mut Text, network: &Network, node: &NodeRepresentation) {
+    if node.knowntopology.nodes.is_empty() {
+        content.push_line("known topology: not received yet");
+        return;
+    }
+    let diff = TopologyDiff::new(network, &node.knowntopology);
+    if diff.is_accurate() {
+        content.push_line(Line::styled(
+            "known topology: accurate",
+            Style::default().fg(ADD_EDGE_COLOR),
+        ));
+        return;
+    }
+    content.push_line("known topology differs from the network:");
+    let wrong_types = diff
+        .wrong_types
+        .iter()
+        .map(|(id, (known, actual))| format!("#{id} {known:?} is {actual:?}"))
+        .collect::<Vec<String>>()
+        .join(", ");
+    for (text, items, color) in [
+        (
+            "missing nodes",
+            format_ids(diff.missing_nodes.iter()),
+            FAILED_COLOR,
+        ),
+        (
+            "unknown nodes",
+            format_ids(diff.unknown_nodes.iter()),
+            CRASH_COLOR,
+        ),
+        (
+            "crashed nodes",
+            format_ids(diff.crashed_nodes.iter()),
+            CRASH_COLOR,
+        ),
+        ("wrong types", wrong_types, HIGHLIGHT_COLOR),
+        (
+            "missing edges",
+            format_edges(diff.missing_edges.iter()),
+            FAILED_COLOR,
+        ),
+        (
+            "stale edges",
+            format_edges(diff.stale_edges.iter()),
+            CRASH_COLOR,
+        ),
+    ] {
+        if !items.is_empty() {
+            content.push_line(Line::from(vec![
+                Span::styled(format!("{text}: "), Style::default().fg(color)),
+                Span::from(items),
+            ]));
+        }
+    }
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    rate.map_or_else(|| "n/a".to_string(), |r| format!("{r:.3}"))
+}
+
+/// draws the pdr observed over time, the seconds in which the pdr was changed are highlighted
+/// and marked below the sparkline
+fn render_drop_sparkline(node: &NodeRepresentation, pdr: f32, area: Rect, frame: &mut Frame) {
+    let block = Block::new().borders(Borders::TOP).title(format!(
+        "observed pdr, {DROP_WINDOW_SECS}s window (config {pdr:.2}, ^ = pdr changed)"
+    ));
+    let inner = block.inner(area);
+    block.render(area, frame.buffer_mut());
+    let [bars, markers] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(inner);
+
+    let series = node
+        .drop_window
+        .series(Instant::now(), u64::from(inner.width));
+    let data: Vec<SparklineBar> = series
+        .iter()
+        .map(|(rate, changed)| {
+            let bar = SparklineBar::from(rate.map(|r| (r * 100.0).round() as u64));
+            if *changed {
+                bar.style(Style::default().fg(HIGHLIGHT_COLOR))
+            } else {
+                bar
+            }
+        })
+        .collect();
+    Sparkline::default()
+        .data(data)
+        .max(100)
+        .style(Style::default().fg(DRONE_COLOR))
+        .render(bars, frame.buffer_mut());
+
+    let marks: String = series
+        .iter()
+        .map(|(_, changed)| if *changed { '^' } else { ' ' })
+        .collect();
+    Line::styled(marks, Style::default().fg(HIGHLIGHT_COLOR)).render(markers, frame.buffer_mut());
+}
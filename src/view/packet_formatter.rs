@@ -11,6 +11,7 @@ use wg_2024::packet::NackType;
 use wg_2024::packet::Packet;
 use wg_2024::packet::PacketType;
 
+use crate::network::reassembly::Session;
 use crate::utilities::theme::{
     BG_COLOR, CHAT_MSG, DISCOVERY_MSG, MEDIA_MSG, MESSAGE_ERROR_COLOR, MESSAGE_REQUEST_COLOR,
     MESSAGE_RESPONSE_COLOR, PACKET_ACK_COLOR, PACKET_FLOOD_REQUEST_COLOR,
@@ -398,3 +399,70 @@ pub fn packet_detail(packet: &Packet) -> Paragraph {
     }
     Paragraph::new(res).wrap(Wrap { trim: true })
 }
+
+/// lists the first indices, the rest are only counted
+fn index_list(indices: &[u64]) -> String {
+    const SHOWN: usize = 10;
+    if indices.is_empty() {
+        return "none".to_string();
+    }
+    let mut res = indices
+        .iter()
+        .take(SHOWN)
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if indices.len() > SHOWN {
+        res.push_str(&format!(" (+{} more)", indices.len() - SHOWN));
+    }
+    res
+}
+
+/// reassembly progress of the session, with the reason the payload could not be decoded if any
+pub fn session_progress(session: &Session) -> Paragraph {
+    let header = if session.is_complete() {
+        Line::styled("Reassembled", Style::new().bg(PACKET_ACK_COLOR))
+    } else {
+        Line::styled("Reassembling", Style::new().bg(PACKET_FRAGMENT_COLOR))
+    };
+    let retransmitted: Vec<u64> = session.retransmitted.iter().copied().collect();
+    let mut res = Text::from(header);
+    res.push_line(format!(
+        "SID: {} source: {} fragments: {}/{}",
+        session.session_id,
+        session.source,
+        session.received(),
+        session.total_n_fragments
+    ));
+    res.push_line(format!("missing: {}", index_list(&session.missing())));
+    res.push_line(format!("retransmitted: {}", index_list(&retransmitted)));
+    match &session.decoded {
+        Some(Ok(_)) => match session.matches_sent() {
+            Some(true) => res.push_line(Line::styled(
+                "matches the sent message",
+                Style::new().bg(PACKET_ACK_COLOR),
+            )),
+            Some(false) => res.push_line(Line::styled(
+                "differs from the sent message",
+                Style::new().bg(PACKET_NACK_COLOR),
+            )),
+            None => {}
+        },
+        Some(Err(e)) => {
+            res.push_line(Line::styled(
+                format!("cannot decode payload: {e}"),
+                Style::new().bg(PACKET_NACK_COLOR),
+            ));
+        }
+        None if session.is_complete() => {
+            let sent = if session.sent.is_some() {
+                ", sent message below"
+            } else {
+                ""
+            };
+            res.push_line(format!("payload: {} bytes{sent}", session.payload().len()));
+        }
+        None => {}
+    }
+    Paragraph::new(res).wrap(Wrap { trim: true })
+}
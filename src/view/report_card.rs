@@ -0,0 +1,92 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, Wrap};
+
+use crate::network::conformance::{report_cards, Rule};
+use crate::network::Network;
+use crate::utilities::theme::{ADD_EDGE_COLOR, BG_COLOR, CRASH_COLOR, HIGHLIGHT_COLOR, TEXT_COLOR};
+
+/// renders one row for each drone implementation with the violations of each rule, and below
+/// the most recent violations of every drone
+pub fn render_report_card(network: &Network, area: Rect, frame: &mut Frame) {
+    let [top, bottom] = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(area);
+
+    let mut header = vec!["implementation", "drones", "events"];
+    header.extend(Rule::ALL.iter().map(|r| r.name()));
+    header.push("result");
+
+    let rows: Vec<Row> = report_cards(network)
+        .iter()
+        .map(|card| {
+            let drones = card
+                .drones
+                .iter()
+                .map(|id| format!("#{id}"))
+                .collect::<Vec<String>>()
+                .join(" ");
+            let mut cells = vec![
+                Cell::from(card.implementation.clone()),
+                Cell::from(drones),
+                Cell::from(card.checked.to_string()),
+            ];
+            for rule in Rule::ALL {
+                let n = card.violations.get(&rule).copied().unwrap_or_default();
+                let style = if n > 0 {
+                    Style::default().fg(CRASH_COLOR)
+                } else {
+                    Style::default().fg(TEXT_COLOR).dim()
+                };
+                cells.push(Cell::from(n.to_string()).style(style));
+            }
+            cells.push(if card.passed() {
+                Cell::from("pass").style(Style::default().fg(ADD_EDGE_COLOR))
+            } else {
+                Cell::from("fail").style(Style::default().fg(CRASH_COLOR).bold())
+            });
+            Row::new(cells)
+        })
+        .collect();
+
+    let mut widths = vec![
+        Constraint::Fill(2),
+        Constraint::Fill(2),
+        Constraint::Length(8),
+    ];
+    widths.extend(
+        Rule::ALL
+            .iter()
+            .map(|r| Constraint::Length(u16::try_from(r.name().len()).unwrap_or(u16::MAX))),
+    );
+    widths.push(Constraint::Length(6));
+
+    let table = Table::new(rows, widths)
+        .header(Row::new(header).style(Style::default().fg(HIGHLIGHT_COLOR).bold()))
+        .block(Block::bordered().title("Report card"))
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR);
+    Widget::render(table, top, frame.buffer_mut());
+
+    let mut lines: Vec<Line> = Vec::new();
+    for node in network.nodes() {
+        for violation in &node.conformance.recent {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("#{} ", node.id),
+                    Style::default().fg(HIGHLIGHT_COLOR),
+                ),
+                Span::from(violation.to_string()),
+            ]));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::styled(
+            "no violations",
+            Style::default().fg(TEXT_COLOR).dim(),
+        ));
+    }
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title("Recent violations"))
+        .bg(BG_COLOR)
+        .fg(TEXT_COLOR)
+        .render(bottom, frame.buffer_mut());
+}
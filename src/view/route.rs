@@ -0,0 +1,76 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use wg_2024::network::NodeId;
+
+use crate::network::Network;
+use crate::utilities::theme::{BG_COLOR, CRASH_COLOR, HIGHLIGHT_COLOR, MARKED_COLOR, TEXT_COLOR};
+
+use super::draw_options::DrawGraphOptions;
+use super::simulation::render_simulation;
+
+/// renders the network with the route on top of it, and below it the list of hops
+pub fn render_route(
+    network: &Network,
+    hops: &[NodeId],
+    hop_index: usize,
+    step: usize,
+    area: Rect,
+    frame: &mut Frame,
+) {
+    let [top, bottom] = Layout::vertical([Constraint::Fill(1), Constraint::Length(5)]).areas(area);
+
+    let title = match hops.len() {
+        0 => "Route, no hops".to_string(),
+        n => format!("Route, hop {} of {n}", step + 1),
+    };
+    let block = Block::bordered().title(title).bg(BG_COLOR).fg(TEXT_COLOR);
+    let inner = block.inner(top);
+    block.render(top, frame.buffer_mut());
+    render_simulation(
+        &DrawGraphOptions::from_route(network, hops, hop_index, step),
+        inner,
+        frame.buffer_mut(),
+    );
+
+    let valid = network.route_hops_valid(hops);
+    let mut spans = Vec::new();
+    for (i, id) in hops.iter().enumerate() {
+        if i > 0 {
+            let style = if valid[i] {
+                Style::default()
+            } else {
+                Style::default().fg(CRASH_COLOR)
+            };
+            spans.push(Span::styled(" -> ", style));
+        }
+        let mut style = if i == step {
+            Style::default().bg(HIGHLIGHT_COLOR).fg(BG_COLOR)
+        } else if i == hop_index {
+            Style::default().bg(MARKED_COLOR).fg(BG_COLOR)
+        } else {
+            Style::default()
+        };
+        if !valid[i] {
+            style = style.fg(CRASH_COLOR).bold();
+        }
+        spans.push(Span::styled(format!("#{id}"), style));
+    }
+    let invalid = valid.iter().filter(|v| !**v).count();
+    let summary = match invalid {
+        0 => Line::from("every hop can still be taken"),
+        n => Line::styled(
+            format!("{n} hops are not in the network anymore"),
+            Style::default().fg(CRASH_COLOR),
+        ),
+    };
+    Paragraph::new(vec![
+        Line::from(spans),
+        Line::from(format!("the packet is at hop {hop_index}")),
+        summary,
+    ])
+    .wrap(Wrap { trim: true })
+    .block(Block::bordered().title("Hops"))
+    .bg(BG_COLOR)
+    .fg(TEXT_COLOR)
+    .render(bottom, frame.buffer_mut());
+}
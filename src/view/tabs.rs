@@ -7,7 +7,11 @@ use ratatui::{
 };
 
 use crate::{
-    network::{node_kind::NodeKind, Network},
+    network::{
+        history::{HistoryPage, HistoryRows},
+        node_kind::NodeKind,
+        Network,
+    },
     screen::Screen,
     utilities::theme::TEXT_COLOR,
 };
@@ -30,10 +34,12 @@ pub fn render_tabs(tab: usize, kind: NodeKind, area: Rect, buf: &mut Buffer) {
         .render(area, buf);
 }
 
+/// renders the table of the tab, or the page of its history when one is given
 pub fn render_tab_content(
     tab: usize,
     screen: &Screen,
     network: &Network,
+    history: Option<&HistoryPage>,
     table_state: &mut TableState,
     area: Rect,
     frame: &mut Frame,
@@ -53,12 +59,16 @@ pub fn render_tab_content(
                 .add_modifier(Modifier::REVERSED)
                 .fg(TEXT_COLOR);
             let header = Row::new(vec!["typ", "←/→", "src", "dst", "sid"]);
-            let rows: Vec<Row<'_>> = match tab {
-                2 => {
+            let rows: Vec<Row<'_>> = match (tab, history.map(|h| &h.rows)) {
+                (_, Some(HistoryRows::Messages(messages))) => messages
+                    .iter()
+                    .map(|(m, finished)| message_table_row(m, *finished))
+                    .collect(),
+                (2, _) => {
                     let mdeque = node.mreceived.iter();
                     mdeque.map(|p| message_table_row(p, true)).collect()
                 }
-                1 => {
+                (1, _) => {
                     let mdeque = node.msent.iter();
 
                     mdeque
@@ -94,7 +104,12 @@ pub fn render_tab_content(
                 2 => node.shortcutted.iter(),
                 _ => unreachable!(),
             };
-            let rows: Vec<Row<'_>> = pdeque.map(|p| packet_table_row(p)).collect();
+            let rows: Vec<Row<'_>> = match history.map(|h| &h.rows) {
+                Some(HistoryRows::Packets(packets)) => {
+                    packets.iter().map(packet_table_row).collect()
+                }
+                _ => pdeque.map(|p| packet_table_row(p)).collect(),
+            };
 
             let table = Table::new(rows, widths)
                 .column_spacing(1)
@@ -56,8 +56,63 @@ pub fn start_dummy_sc_from_cfg(
 
 #[cfg(feature = "integration_tests")]
 pub fn start_dummy_sc_from_cfg_with_handles(
+    config: &str,
+    node_handles: HashMap<u8, JoinHandle<()>>,
+) -> (
+    Sender<KeyEvent>,
+    JoinHandle<()>,
+    Sender<DroneEvent>,
+    Sender<NodeEvent>,
+    HashMap<NodeId, Receiver<DroneCommand>>,
+    HashMap<NodeId, Receiver<Packet>>,
+) {
+    start_dummy_sc(config, node_handles, true)
+}
+
+/// like `start_dummy_sc_from_cfg`, but malformed events are recorded as incidents and nodes
+/// that exit are marked as failed, instead of making the sc panic
+#[cfg(feature = "integration_tests")]
+#[allow(clippy::type_complexity)]
+pub fn start_lenient_sc_from_cfg(
+    config: &str,
+) -> (
+    Sender<KeyEvent>,
+    JoinHandle<()>,
+    Sender<DroneEvent>,
+    Sender<NodeEvent>,
+    HashMap<NodeId, Receiver<DroneCommand>>,
+    HashMap<NodeId, Receiver<Packet>>,
+) {
+    start_dummy_sc(config, HashMap::new(), false)
+}
+
+/// starts a sc with the given node handles, or with dummy ones if empty, `strict` makes it
+/// panic on malformed events and unexpected thread exits
+#[cfg(feature = "integration_tests")]
+#[allow(clippy::type_complexity)]
+pub fn start_dummy_sc(
+    config: &str,
+    node_handles: HashMap<u8, JoinHandle<()>>,
+    strict: bool,
+) -> (
+    Sender<KeyEvent>,
+    JoinHandle<()>,
+    Sender<DroneEvent>,
+    Sender<NodeEvent>,
+    HashMap<NodeId, Receiver<DroneCommand>>,
+    HashMap<NodeId, Receiver<Packet>>,
+) {
+    start_configured_sc(config, node_handles, strict, |_| {})
+}
+
+/// like `start_dummy_sc`, `configure` is called on the sc before it starts
+#[cfg(feature = "integration_tests")]
+#[allow(clippy::type_complexity)]
+pub fn start_configured_sc(
     config: &str,
     mut node_handles: HashMap<u8, JoinHandle<()>>,
+    strict: bool,
+    configure: impl FnOnce(&mut MySimulationController),
 ) -> (
     Sender<KeyEvent>,
     JoinHandle<()>,
@@ -148,6 +203,19 @@ pub fn start_dummy_sc_from_cfg_with_handles(
     let terminal = Terminal::new(TestBackend::new(50, 50)).unwrap();
     let mut simcontr = MySimulationController::new(opt);
     simcontr.set_keyevent_recv(keyevent_recv);
+    simcontr.set_strict(strict);
+    // the dummy node threads never exit, so quitting must not wait for them
+    simcontr.set_shutdown_timeout(std::time::Duration::from_millis(20));
+    // statistics are exported on quit, each test keeps them apart from the others
+    let test_name = thread::current()
+        .name()
+        .unwrap_or("unnamed")
+        .replace("::", "-");
+    simcontr.set_stats_dir(format!(
+        "{}/sc_stats-{test_name}",
+        env!("CARGO_TARGET_TMPDIR")
+    ));
+    configure(&mut simcontr);
     let join_handle = thread::spawn(move || {
         //simcontr.run();
         simcontr.run_with_terminal(terminal);
@@ -362,3 +430,11 @@ pub fn all_the_packet_types(from: u8) -> Vec<PacketType> {
         }),
     ]
 }
+
+#[cfg(feature = "integration_tests")]
+pub fn send_keys(keyevent_send: &Sender<KeyEvent>, text: &str) {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    for c in text.chars() {
+        let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+}
@@ -851,3 +851,1093 @@ fn pushdown_test_drone() {
         panic!("sc is not finished 100ms after quit mesage");
     }
 }
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn help_overlay() {
+    use common::{expect_command_hmap, expect_no_commands};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    // while the overlay is shown the keys of the window underneath are ignored
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::Crash);
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if !sc_handle.is_finished() {
+        panic!("sc is not finished after quit message from the help overlay");
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn command_prompt() {
+    use common::{expect_no_commands, send_keys};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    send_keys(&keyevent_send, ":pdr 1 0.5");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::SetPacketDropRate(0.5));
+
+    // run again the last command from the history
+    send_keys(&keyevent_send, ":");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::SetPacketDropRate(0.5));
+
+    // F1 shows the help over the prompt, closing it keeps what was typed
+    send_keys(&keyevent_send, ":pdr 1 0.2");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::SetPacketDropRate(0.2));
+
+    // errors are shown inline, and the 'q' is typed instead of quitting
+    send_keys(&keyevent_send, ":crash 5");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    send_keys(&keyevent_send, "q");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+
+    // complete the second id with tab
+    send_keys(&keyevent_send, ":connect 1 ");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let command = command_receivers.get(&1).unwrap().try_recv().unwrap();
+    if !matches!(command, DroneCommand::AddSender(3, _)) {
+        panic!("unexpected command : {:?}", command);
+    }
+    let command = command_receivers.get(&3).unwrap().try_recv().unwrap();
+    if !matches!(command, DroneCommand::AddSender(1, _)) {
+        panic!("unexpected command : {:?}", command);
+    }
+
+    // the exported topology can be read back as a config
+    let path = format!("{}/command_prompt_export.toml", env!("CARGO_TARGET_TMPDIR"));
+    send_keys(&keyevent_send, &format!(":export topology {path}"));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let exported: wg_2024::config::Config =
+        toml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(exported.drone.len(), 4);
+    assert_eq!(exported.drone[0].connected_node_ids, vec![2, 3]);
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn batch_operations() {
+    use common::{expect_command, expect_no_command, expect_no_commands};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    // mark drones 3 and 4 and change their pdr together
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('P'), KeyModifiers::SHIFT));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    for id in [3, 4] {
+        expect_command(
+            command_receivers.get(&id).unwrap(),
+            &DroneCommand::SetPacketDropRate(0.02),
+        );
+    }
+    expect_no_commands(&command_receivers);
+
+    // crashing both 3 and 4 disconnects the network, so none of them crashes
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+    // crashing 2 alone is not allowed, but crashing it together with 1 is
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command(command_receivers.get(&1).unwrap(), &DroneCommand::Crash);
+    expect_command(command_receivers.get(&2).unwrap(), &DroneCommand::Crash);
+    expect_command(
+        command_receivers.get(&3).unwrap(),
+        &DroneCommand::RemoveSender(2),
+    );
+    expect_no_command(command_receivers.get(&1).unwrap());
+    expect_no_commands(&command_receivers);
+
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn pdr_typing_and_sweeps() {
+    use common::{expect_command, expect_no_commands, send_keys};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    // an invalid typed pdr is rejected, then it is corrected
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+    send_keys(&keyevent_send, "1.5");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+    for _ in 0..3 {
+        let _ = keyevent_send.send(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    send_keys(&keyevent_send, "0.9");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command(
+        command_receivers.get(&1).unwrap(),
+        &DroneCommand::SetPacketDropRate(0.9),
+    );
+    expect_no_commands(&command_receivers);
+
+    // step drone 2 through two values
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE));
+    send_keys(&keyevent_send, "sweep 2 steps 0.1 0.2,0.4");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME * 2));
+    expect_command(
+        command_receivers.get(&2).unwrap(),
+        &DroneCommand::SetPacketDropRate(0.2),
+    );
+    expect_command(
+        command_receivers.get(&2).unwrap(),
+        &DroneCommand::SetPacketDropRate(0.4),
+    );
+    expect_no_commands(&command_receivers);
+
+    // a ramp on a crashed drone is rejected
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE));
+    send_keys(&keyevent_send, "crash 1");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE));
+    send_keys(&keyevent_send, "sweep 1 ramp 0 1 1");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command(command_receivers.get(&1).unwrap(), &DroneCommand::Crash);
+    expect_command(
+        command_receivers.get(&2).unwrap(),
+        &DroneCommand::RemoveSender(1),
+    );
+    expect_no_commands(&command_receivers);
+
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn chaos_same_seed() {
+    use common::send_keys;
+
+    // runs the chaos engine on a new sc and returns the commands that every node received
+    let run = |seed: u64| {
+        let (
+            keyevent_send,
+            sc_handle,
+            _droneevent_send,
+            _nodeevent_send,
+            command_receivers,
+            _packet_receivers,
+        ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+        let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE));
+        send_keys(
+            &keyevent_send,
+            &format!("chaos {seed} interval=0-0.01 faults=5"),
+        );
+        let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        thread::sleep(Duration::from_millis(WAITING_TIME));
+        if sc_handle.is_finished() {
+            panic!("sc should still be running");
+        }
+        let mut received: Vec<(u8, String)> = Vec::new();
+        for id in 1..=6 {
+            while let Ok(c) = command_receivers.get(&id).unwrap().try_recv() {
+                // senders can't be compared between two runs
+                let c = match c {
+                    DroneCommand::AddSender(n, _) => format!("AddSender({n})"),
+                    c => format!("{c:?}"),
+                };
+                received.push((id, c));
+            }
+        }
+        received
+    };
+
+    let first = run(9);
+    assert!(first.len() >= 5, "chaos injected too few faults: {first:?}");
+    assert_eq!(first, run(9));
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn export_stats() {
+    use common::send_keys;
+    use wg_2024::network::SourceRoutingHeader;
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    let fragment = PacketType::MsgFragment(Fragment {
+        fragment_index: 0,
+        total_n_fragments: 1,
+        length: 128,
+        data: [0; 128],
+    });
+    // drone 2 forwards a fragment to 3, that drops it
+    let _ = droneevent_send.send(DroneEvent::PacketSent(Packet {
+        pack_type: fragment.clone(),
+        routing_header: SourceRoutingHeader {
+            hop_index: 2,
+            hops: vec![1, 2, 3],
+        },
+        session_id: 0,
+    }));
+    let _ = droneevent_send.send(DroneEvent::PacketDropped(Packet {
+        pack_type: fragment,
+        routing_header: SourceRoutingHeader {
+            hop_index: 2,
+            hops: vec![1, 2, 3],
+        },
+        session_id: 0,
+    }));
+    // drone 4 drops an ack, which is not a fragment so it does not count towards its pdr
+    let _ = droneevent_send.send(DroneEvent::PacketDropped(Packet {
+        pack_type: PacketType::Ack(wg_2024::packet::Ack { fragment_index: 0 }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![3, 4, 5],
+        },
+        session_id: 0,
+    }));
+    let message = Message {
+        source: 5,
+        destination: 6,
+        session_id: 1,
+        content: MessageType::Request(RequestType::TextRequest(TextRequest::TextList)),
+    };
+    let _ = nodeevent_send.send(NodeEvent::StartingMessageTransmission(message.clone()));
+    let _ = nodeevent_send.send(NodeEvent::MessageSentSuccessfully(message.clone()));
+    let _ = nodeevent_send.send(NodeEvent::MessageReceived(message));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+
+    let dir = format!("{}/export_stats", env!("CARGO_TARGET_TMPDIR"));
+    send_keys(&keyevent_send, &format!(":export stats {dir}"));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+
+    let nodes = std::fs::read_to_string(format!("{dir}/nodes.csv")).unwrap();
+    let lines: Vec<&str> = nodes.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert!(lines[2].starts_with("2,drone,\"drone#2\",false,0,0,1,0,1,0,0,0,0,"));
+    assert!(lines[3].starts_with("3,drone,\"drone#3\",false,0,1,1,1,0,0,0,0,0,1,0,0,0,0,"));
+    assert!(lines[5].ends_with(",1,1,0"));
+    assert!(lines[6].ends_with(",0,0,1"));
+
+    let edges = std::fs::read_to_string(format!("{dir}/edges.csv")).unwrap();
+    assert!(edges.contains("\n2,3,true,1,0,0,0,0\n"));
+
+    let messages = std::fs::read_to_string(format!("{dir}/messages.csv")).unwrap();
+    assert!(messages.contains("\n5,started,request/text,1\n"));
+    assert!(messages.contains("\n6,received,request/text,1\n"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(format!("{dir}/stats.json")).unwrap())
+            .unwrap();
+    assert_eq!(json["nodes"][2]["observed_pdr"], 1.0);
+    assert_eq!(json["nodes"][3]["observed_pdr"], serde_json::Value::Null);
+    assert_eq!(json["nodes"][3]["frags_dropped"], 0);
+    assert_eq!(json["nodes"][4]["messages_completed"]["request/text"], 1);
+    assert_eq!(json["edges"].as_array().unwrap().len(), 6);
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn metrics_window() {
+    use common::expect_no_commands;
+    use wg_2024::network::SourceRoutingHeader;
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE));
+    // traffic is drawn while the window is open
+    let _ = droneevent_send.send(DroneEvent::PacketSent(Packet {
+        pack_type: PacketType::MsgFragment(Fragment {
+            fragment_index: 0,
+            total_n_fragments: 1,
+            length: 128,
+            data: [0; 128],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 2,
+            hops: vec![1, 2, 3],
+        },
+        session_id: 0,
+    }));
+    let message = Message {
+        source: 5,
+        destination: 6,
+        session_id: 1,
+        content: MessageType::Request(RequestType::TextRequest(TextRequest::TextList)),
+    };
+    let _ = nodeevent_send.send(NodeEvent::StartingMessageTransmission(message));
+    // the keys of the main window are ignored, the arrows select the node of the breakdown
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+    expect_just_command_hmap(
+        &command_receivers,
+        2,
+        &DroneCommand::SetPacketDropRate(0.01),
+    );
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn incidents_instead_of_panics() {
+    use common::start_lenient_sc_from_cfg;
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{FloodRequest, FloodResponse};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_lenient_sc_from_cfg("./tests/config_files/line.toml");
+
+    let flood = PacketType::FloodRequest(FloodRequest {
+        flood_id: 0,
+        initiator_id: 0,
+        path_trace: vec![],
+    });
+    let empty = SourceRoutingHeader {
+        hop_index: 1,
+        hops: vec![],
+    };
+    let _ = nodeevent_send.send(NodeEvent::PacketSent(Packet {
+        pack_type: flood.clone(),
+        routing_header: empty.clone(),
+        session_id: 0,
+    }));
+    let _ = nodeevent_send.send(NodeEvent::MessageSentSuccessfully(Message {
+        source: 5,
+        destination: 6,
+        session_id: 0,
+        content: MessageType::Request(RequestType::TextRequest(TextRequest::TextList)),
+    }));
+    let _ = droneevent_send.send(DroneEvent::PacketSent(Packet {
+        pack_type: flood,
+        routing_header: empty,
+        session_id: 0,
+    }));
+    let _ = droneevent_send.send(DroneEvent::ControllerShortcut(Packet {
+        pack_type: PacketType::FloodResponse(FloodResponse {
+            flood_id: 0,
+            path_trace: vec![],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![1, 2, 3, 4, 100],
+        },
+        session_id: 0,
+    }));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should have recorded the incidents and kept running");
+    }
+
+    // the incidents can be browsed, then the sc works as usual
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::Crash);
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    match sc_handle.join() {
+        Ok(()) => {}
+        Err(_) => panic!("sc should have exited normally"),
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn survive_thread_exit() {
+    use std::collections::HashMap;
+
+    use common::{expect_no_commands, start_dummy_sc};
+
+    let failed_thread = thread::spawn(move || panic!("buggy drone"));
+    let h = HashMap::from([(1, failed_thread)]);
+
+    let (
+        keyevent_send,
+        sc_handle,
+        _droneevent_send,
+        _nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc("./tests/config_files/line.toml", h, false);
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should keep running after a node thread exits");
+    }
+    expect_just_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+
+    // the failed drone counts as crashed, so it can't be crashed again
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    match sc_handle.join() {
+        Ok(()) => {}
+        Err(_) => panic!("sc should have exited normally"),
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn thread_exit_while_changing_pdr() {
+    use std::collections::HashMap;
+
+    use common::{expect_no_commands, start_dummy_sc};
+
+    let failed_thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(2 * WAITING_TIME));
+        panic!("buggy drone");
+    });
+    let h = HashMap::from([(1, failed_thread)]);
+
+    let (
+        keyevent_send,
+        sc_handle,
+        _droneevent_send,
+        _nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc("./tests/config_files/line.toml", h, false);
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(4 * WAITING_TIME));
+    expect_just_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+
+    // the window was closed when the drone failed, so the pdr is not sent
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+    if sc_handle.is_finished() {
+        panic!("sc should keep running after the drone failed");
+    }
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    match sc_handle.join() {
+        Ok(()) => {}
+        Err(_) => panic!("sc should have exited normally"),
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn revive() {
+    use common::{expect_no_commands, send_keys};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        _droneevent_send,
+        _nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    // a drone that did not crash can't be revived
+    send_keys(&keyevent_send, ":revive 1");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::Crash);
+
+    // without neighbors the network would not be connected
+    send_keys(&keyevent_send, ":revive 1 neighbors=");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_no_commands(&command_receivers);
+
+    // the previous neighbor is told to add the new drone back
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let command = command_receivers.get(&2).unwrap().try_recv().unwrap();
+    if !matches!(command, DroneCommand::AddSender(1, _)) {
+        panic!("unexpected command : {:?}", command);
+    }
+    expect_no_commands(&command_receivers);
+
+    // and it can be crashed again, the command goes to the new thread
+    send_keys(&keyevent_send, ":crash 1");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_just_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn shutdown_crashes_drones() {
+    use common::{expect_command, expect_no_command};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        _droneevent_send,
+        _nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::Crash);
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if !sc_handle.is_finished() {
+        panic!("sc should not wait for the node threads longer than the shutdown timeout");
+    }
+    // the drone that already crashed is not crashed again
+    expect_no_command(&command_receivers[&1]);
+    for id in [2, 3, 4] {
+        expect_command(&command_receivers[&id], &DroneCommand::Crash);
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn shortcut_policies() {
+    use common::send_keys;
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{Ack, Packet};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        _nodeevent_send,
+        _command_receivers,
+        packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    // drone 3 could not reach drone 4
+    let packet = Packet {
+        pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 3,
+            hops: vec![1, 2, 3, 4, 6],
+        },
+        session_id: 0,
+    };
+
+    send_keys(&keyevent_send, ":shortcut drop");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = droneevent_send.send(DroneEvent::ControllerShortcut(packet.clone()));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    for r in packet_receivers.values() {
+        assert!(r.try_recv().is_err(), "dropped packet was sent");
+    }
+
+    // the rest of the route is replaced with the path from drone 3 to server 6
+    send_keys(&keyevent_send, ":shortcut reroute");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = droneevent_send.send(DroneEvent::ControllerShortcut(packet.clone()));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let mut rerouted = packet.clone();
+    rerouted.routing_header.hops = vec![1, 2, 3, 6];
+    expect_just_packet_hmap(&packet_receivers, 6, &rerouted);
+
+    // delayed packets are sent as usual, only later
+    send_keys(&keyevent_send, ":shortcut delay 0.5");
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = droneevent_send.send(DroneEvent::ControllerShortcut(packet.clone()));
+    thread::sleep(Duration::from_millis(100));
+    for r in packet_receivers.values() {
+        assert!(r.try_recv().is_err(), "delayed packet was sent too early");
+    }
+    thread::sleep(Duration::from_millis(600));
+    let mut delivered = packet;
+    delivered.routing_header.hop_index = 4;
+    expect_just_packet_hmap(&packet_receivers, 6, &delivered);
+
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn floods_window() {
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{FloodRequest, FloodResponse};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        _command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    let request = |path_trace: Vec<(u8, NodeType)>| Packet {
+        pack_type: PacketType::FloodRequest(FloodRequest {
+            flood_id: 1,
+            initiator_id: 5,
+            path_trace,
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![],
+        },
+        session_id: 0,
+    };
+    // the source of a node event is taken from its route
+    let mut first = request(vec![(5, NodeType::Client)]);
+    first.routing_header.hops = vec![5, 4];
+    let _ = nodeevent_send.send(NodeEvent::PacketSent(first));
+    let _ = droneevent_send.send(DroneEvent::PacketSent(request(vec![
+        (5, NodeType::Client),
+        (4, NodeType::Drone),
+    ])));
+    let _ = droneevent_send.send(DroneEvent::PacketSent(Packet {
+        pack_type: PacketType::FloodResponse(FloodResponse {
+            flood_id: 1,
+            path_trace: vec![
+                (5, NodeType::Client),
+                (4, NodeType::Drone),
+                (6, NodeType::Server),
+            ],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![6, 4, 5],
+        },
+        session_id: 0,
+    }));
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    match sc_handle.join() {
+        Ok(()) => {}
+        Err(_) => panic!("sc should have exited normally"),
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn follow_route() {
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::Ack;
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        _nodeevent_send,
+        command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    // without packets there is no route to follow
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+
+    // the route goes through 7, which is not in the network
+    let _ = droneevent_send.send(DroneEvent::PacketSent(Packet {
+        pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![1, 2, 7, 3],
+        },
+        session_id: 0,
+    }));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE));
+    for code in [
+        KeyCode::Right,
+        KeyCode::Right,
+        KeyCode::Right,
+        KeyCode::Left,
+    ] {
+        let _ = keyevent_send.send(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+
+    // Enter goes back to the detail window, where the drone can be crashed
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    expect_command_hmap(&command_receivers, 2, &DroneCommand::RemoveSender(1));
+    expect_just_command_hmap(&command_receivers, 1, &DroneCommand::Crash);
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn fragment_reassembly() {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use common::start_configured_sc;
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::Fragment;
+
+    // payloads given to the decoder, which is a plain fn so it can only record them in a static
+    static DECODED: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+    fn decode(payload: &[u8]) -> Result<Message, String> {
+        DECODED.lock().unwrap().push(payload.to_vec());
+        Err("test decoder".to_string())
+    }
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        nodeevent_send,
+        _command_receivers,
+        _packet_receivers,
+    ) = start_configured_sc(
+        "./tests/config_files/line.toml",
+        HashMap::new(),
+        false,
+        |sc| sc.set_payload_decoder(decode),
+    );
+
+    let fragment = |fragment_index: u64, hop_index: usize| Packet {
+        pack_type: PacketType::MsgFragment(Fragment {
+            fragment_index,
+            total_n_fragments: 3,
+            length: 4,
+            data: [1; 128],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index,
+            hops: vec![5, 4, 3, 2, 1],
+        },
+        session_id: 9,
+    };
+    // fragment 0 is sent twice, fragment 1 is never sent
+    for index in [0, 2, 0] {
+        let _ = nodeevent_send.send(NodeEvent::PacketSent(fragment(index, 1)));
+    }
+    // the detail of the packet dropped by 1 shows the session it belongs to
+    let _ = droneevent_send.send(DroneEvent::PacketDropped(fragment(2, 4)));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    assert!(
+        DECODED.lock().unwrap().is_empty(),
+        "an incomplete session should not be decoded"
+    );
+
+    // once complete the payload is shown
+    let _ = nodeevent_send.send(NodeEvent::PacketSent(fragment(1, 1)));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+    // the three fragments of 4 bytes are joined once, the retransmission is not repeated
+    assert_eq!(*DECODED.lock().unwrap(), vec![vec![1; 12]]);
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn nacks_window() {
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::{Nack, NackType};
+
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        _nodeevent_send,
+        _command_receivers,
+        _packet_receivers,
+    ) = start_dummy_sc_from_cfg("./tests/config_files/line.toml");
+
+    let nack = |nack_type: NackType, hop_index: usize| Packet {
+        pack_type: PacketType::Nack(Nack {
+            fragment_index: 0,
+            nack_type,
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index,
+            hops: vec![3, 4, 5],
+        },
+        session_id: 2,
+    };
+    // created by 3, then forwarded by 4
+    let _ = droneevent_send.send(DroneEvent::PacketSent(nack(NackType::ErrorInRouting(2), 1)));
+    let _ = droneevent_send.send(DroneEvent::PacketSent(nack(NackType::ErrorInRouting(2), 2)));
+    let _ = droneevent_send.send(DroneEvent::ControllerShortcut(nack(NackType::Dropped, 1)));
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn overlapping_messages_with_small_retention() {
+    use std::collections::HashMap;
+
+    use ap24_simulation_controller::Retention;
+    use common::start_configured_sc;
+
+    // strict, so a message wrongly reported as never started would make the sc panic
+    let (
+        keyevent_send,
+        sc_handle,
+        _droneevent_send,
+        nodeevent_send,
+        _command_receivers,
+        _packet_receivers,
+    ) = start_configured_sc(
+        "./tests/config_files/line.toml",
+        HashMap::new(),
+        true,
+        |sc| sc.set_retention(Retention::uniform(1)),
+    );
+    let message = |session_id| Message {
+        source: 5,
+        destination: 6,
+        session_id,
+        content: MessageType::Request(RequestType::TextRequest(TextRequest::TextList)),
+    };
+    // the first message is still in flight when the second one starts
+    let _ = nodeevent_send.send(NodeEvent::StartingMessageTransmission(message(1)));
+    let _ = nodeevent_send.send(NodeEvent::StartingMessageTransmission(message(2)));
+    let _ = nodeevent_send.send(NodeEvent::MessageSentSuccessfully(message(1)));
+    let _ = nodeevent_send.send(NodeEvent::MessageSentSuccessfully(message(2)));
+    let _ = nodeevent_send.send(NodeEvent::StartingMessageTransmission(message(3)));
+    let _ = nodeevent_send.send(NodeEvent::MessageSentSuccessfully(message(3)));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    if sc_handle.is_finished() {
+        panic!("sc should accept messages that were moved out of memory while in flight");
+    }
+
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+    match sc_handle.join() {
+        Ok(()) => {}
+        Err(_) => panic!("sc should have exited normally"),
+    }
+}
+
+#[test]
+#[cfg(feature = "integration_tests")]
+fn history_paging() {
+    use ap24_simulation_controller::Retention;
+    use common::start_configured_sc;
+    use std::collections::HashMap;
+    use wg_2024::network::SourceRoutingHeader;
+    use wg_2024::packet::Ack;
+
+    let dir = std::env::temp_dir().join(format!("sc-history-paging-{}", std::process::id()));
+    let history_dir = dir.clone();
+    let (
+        keyevent_send,
+        sc_handle,
+        droneevent_send,
+        _nodeevent_send,
+        _command_receivers,
+        _packet_receivers,
+    ) = start_configured_sc(
+        "./tests/config_files/line.toml",
+        HashMap::new(),
+        true,
+        move |sc| {
+            sc.set_history_dir(history_dir).unwrap();
+            sc.set_retention(Retention {
+                sent: 2,
+                ..Retention::uniform(1000)
+            });
+        },
+    );
+
+    for fragment_index in 0..5 {
+        let _ = droneevent_send.send(DroneEvent::PacketSent(Packet {
+            pack_type: PacketType::Ack(Ack { fragment_index }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 1,
+                hops: vec![1, 2],
+            },
+            session_id: 0,
+        }));
+    }
+    thread::sleep(Duration::from_millis(WAITING_TIME));
+
+    // page back past the last page, follow a route from the history and come back
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+    for code in [
+        KeyCode::PageUp,
+        KeyCode::PageUp,
+        KeyCode::Down,
+        KeyCode::Char('o'),
+        KeyCode::Enter,
+        KeyCode::PageDown,
+        KeyCode::PageDown,
+    ] {
+        let _ = keyevent_send.send(KeyEvent::new(code, KeyModifiers::NONE));
+        thread::sleep(Duration::from_millis(WAITING_TIME));
+    }
+    if sc_handle.is_finished() {
+        panic!("sc should still be running");
+    }
+
+    // the history is kept after quitting
+    let _ = keyevent_send.send(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    sc_handle.join().unwrap();
+    let log = std::fs::read_to_string(dir.join("1_sent.jsonl")).unwrap();
+    assert_eq!(log.lines().count(), 3);
+    let _ = std::fs::remove_dir_all(dir);
+}